@@ -3,7 +3,7 @@ mod storage_mdbx {
     pub use self::error::Result;
     use digest::{Digest, Output};
     use libmdbx::{Database, Geometry, NoWriteMap, WriteFlags};
-    use patricia_merkle_tree::{Encode, PatriciaMerkleTree};
+    use patricia_merkle_tree::{value_store::ValueStore, Encode, PatriciaMerkleTree};
     use rand::{rngs::StdRng, RngCore, SeedableRng};
     use serde::{Deserialize, Serialize};
     use sha3::Keccak256;
@@ -26,12 +26,50 @@ mod storage_mdbx {
             Bincode(#[from] bincode::Error),
             #[error(transparent)]
             Mdbx(#[from] libmdbx::Error),
+            #[error(transparent)]
+            Tree(#[from] patricia_merkle_tree::error::Error),
         }
     }
 
     type TreeDB = Database<NoWriteMap>;
 
-    struct StorageRef<P, V, H>(pub Rc<TreeDB>, pub Uuid, pub PhantomData<(P, V, H)>)
+    /// A [`ValueStore`] backed by an MDBX table, keyed by the raw bytes of a [`Uuid`].
+    ///
+    /// This is the one piece of this example that actually talks to libmdbx: `MdbxStorageTree`
+    /// reaches its values only through the `ValueStore` methods. Its `store` field and the
+    /// `load_value`/`store_value` helpers name `MdbxValueStore` itself, though, so swapping in
+    /// RocksDB or sled means writing a new `ValueStore` impl and changing those types as well.
+    #[derive(Clone)]
+    struct MdbxValueStore(Rc<TreeDB>);
+
+    impl ValueStore for MdbxValueStore {
+        type Error = error::Error;
+
+        fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+            let tx = self.0.begin_ro_txn()?;
+            let table = tx.open_table(None)?;
+            let value: Option<Cow<[u8]>> = tx.get(&table, key)?;
+            Ok(value.map(|value| value.into_owned()))
+        }
+
+        fn put(&mut self, key: &[u8], bytes: Vec<u8>) -> Result<()> {
+            let tx = self.0.begin_rw_txn()?;
+            let table = tx.open_table(None)?;
+            tx.put(&table, key, bytes, WriteFlags::empty())?;
+            tx.commit()?;
+            Ok(())
+        }
+
+        fn del(&mut self, key: &[u8]) -> Result<()> {
+            let tx = self.0.begin_rw_txn()?;
+            let table = tx.open_table(None)?;
+            tx.del(&table, key, None)?;
+            tx.commit()?;
+            Ok(())
+        }
+    }
+
+    struct StorageRef<P, V, H>(pub MdbxValueStore, pub Uuid, pub PhantomData<(P, V, H)>, pub Vec<u8>)
     where
         P: Encode,
         V: Encode + Serialize + for<'de> Deserialize<'de>,
@@ -43,9 +81,11 @@ mod storage_mdbx {
         V: Encode + Serialize + for<'de> Deserialize<'de>,
         H: Digest,
     {
+        // The encoded bytes are computed once, up front, in `MdbxStorageTree::insert` (see its
+        // 4th field) and cached here, so this no longer needs to re-fetch the backing record
+        // (and potentially find it missing or corrupt) on every call.
         fn encode(&self) -> Cow<[u8]> {
-            let value: V = MdbxStorageTree::<P, V, H>::load_value(&self.0, &self.1).unwrap();
-            Cow::Owned(value.encode().into_owned())
+            Cow::Borrowed(&self.3)
         }
     }
 
@@ -56,7 +96,7 @@ mod storage_mdbx {
         H: Digest,
     {
         tree: PatriciaMerkleTree<P, StorageRef<P, V, H>, H>,
-        db: Rc<Database<NoWriteMap>>,
+        store: MdbxValueStore,
     }
 
     impl<P, V, H> MdbxStorageTree<P, V, H>
@@ -76,7 +116,7 @@ mod storage_mdbx {
 
             Ok(Self {
                 tree: PatriciaMerkleTree::new(),
-                db: Rc::new(db),
+                store: MdbxValueStore(Rc::new(db)),
             })
         }
 
@@ -84,17 +124,21 @@ mod storage_mdbx {
         pub fn get(&self, path: &P) -> Result<Option<V>> {
             self.tree
                 .get(path)
-                .map(|storage_key| Self::load_value(&self.db, &storage_key.1))
+                .map(|storage_key| load_value(&self.store, &storage_key.1))
                 .transpose()
         }
 
         pub fn insert(&mut self, path: P, value: V) -> Result<Option<V>> {
-            let storage_key = Self::store_value(&self.db, value)?;
+            let encoded = value.encode().into_owned();
+            let storage_key = store_value(&mut self.store, value)?;
             self.tree
-                .insert(path, StorageRef(self.db.clone(), storage_key, PhantomData))
+                .insert(
+                    path,
+                    StorageRef(self.store.clone(), storage_key, PhantomData, encoded),
+                )
                 .map(|storage_key| {
-                    let value = Self::load_value(&self.db, &storage_key.1)?;
-                    Self::erase_value(&self.db, &storage_key.1)?;
+                    let value = load_value(&self.store, &storage_key.1)?;
+                    self.store.del(storage_key.1.as_bytes().as_slice())?;
                     Ok(value)
                 })
                 .transpose()
@@ -103,37 +147,30 @@ mod storage_mdbx {
         pub fn compute_hash(&mut self) -> &Output<H> {
             self.tree.compute_hash()
         }
+    }
 
-        fn load_value(db: &TreeDB, storage_key: &Uuid) -> Result<V> {
-            let tx = db.begin_ro_txn()?;
-            let table = tx.open_table(None)?;
-
-            let value: Cow<[u8]> = tx
-                .get(&table, storage_key.as_bytes().as_slice())?
-                .expect("value to be there");
-
-            bincode::deserialize(&value).map_err(Into::into)
-        }
-
-        fn erase_value(db: &TreeDB, storage_key: &Uuid) -> Result<()> {
-            let tx = db.begin_rw_txn()?;
-            let table = tx.open_table(None)?;
-            tx.del(&table, storage_key, None)?;
-            tx.commit()?;
-            Ok(())
-        }
-
-        fn store_value(db: &TreeDB, value: V) -> Result<Uuid> {
-            let storage_key = Uuid::new_v4();
-            let value = bincode::serialize(&value)?;
-
-            let tx = db.begin_rw_txn()?;
-            let table = tx.open_table(None)?;
-            tx.put(&table, storage_key, value, WriteFlags::empty())?;
-            tx.commit()?;
+    /// Fetch and bincode-decode the value stored under `storage_key`.
+    ///
+    /// Returns [`error::Error::Tree`] (wrapping [`patricia_merkle_tree::error::Error::MissingNode`])
+    /// instead of panicking if the record is gone, and [`error::Error::Bincode`] if it's there but
+    /// doesn't decode — a disk-backed store can hit either, unlike the in-memory tree where a
+    /// `ValueRef` is always valid.
+    fn load_value<V: for<'de> Deserialize<'de>>(
+        store: &MdbxValueStore,
+        storage_key: &Uuid,
+    ) -> Result<V> {
+        let bytes = store.get(storage_key.as_bytes().as_slice())?.ok_or(
+            patricia_merkle_tree::error::Error::MissingNode(storage_key.as_bytes().to_vec()),
+        )?;
+        bincode::deserialize(&bytes).map_err(Into::into)
+    }
 
-            Ok(storage_key)
-        }
+    /// Bincode-encode `value` and store it under a freshly generated key.
+    fn store_value<V: Serialize>(store: &mut MdbxValueStore, value: V) -> Result<Uuid> {
+        let storage_key = Uuid::new_v4();
+        let bytes = bincode::serialize(&value)?;
+        store.put(storage_key.as_bytes().as_slice(), bytes)?;
+        Ok(storage_key)
     }
 
     pub fn run() -> Result<()> {
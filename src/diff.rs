@@ -0,0 +1,176 @@
+//! Structural comparison between two trees sharing `<P, V, H>`, for debugging state-root
+//! mismatches between two tree instances — in the spirit of Polygon's `mpt_trie`
+//! `debug_tools::diff`.
+//!
+//! [`diff`] walks both trees in lock-step, descending into matching child slots, and stops at the
+//! first point their structure or contents disagree, reporting the deepest path reached in common
+//! along with what each side looks like there.
+
+use crate::{
+    nibble::{Nibble, NibbleVec},
+    node::Node,
+    NodeRef, NodesStorage, PatriciaMerkleTree, ValuesStorage,
+};
+use digest::Digest;
+
+/// What was found at a [`DiffPoint`], on one side of the comparison.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NodeInfo {
+    Branch,
+    Extension,
+    Leaf,
+    /// This side has no node in this slot, while the other side does.
+    Missing,
+}
+
+/// The deepest node path at which two trees agree, and what each one looks like just past it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DiffPoint {
+    /// The nibble path shared by both trees up to (but not including) the divergence.
+    pub path: NibbleVec,
+    /// `path`'s length in nibbles.
+    pub depth: usize,
+    pub a: NodeInfo,
+    pub b: NodeInfo,
+}
+
+/// Find the deepest point at which `a` and `b` diverge, or `None` if they are structurally and
+/// value-wise identical.
+pub fn diff<P, V, H>(
+    a: &PatriciaMerkleTree<P, V, H>,
+    b: &PatriciaMerkleTree<P, V, H>,
+) -> Option<DiffPoint>
+where
+    P: AsRef<[u8]>,
+    V: AsRef<[u8]> + Eq,
+    H: Digest,
+{
+    let mut path = Vec::new();
+
+    let node_a = a.root_ref.is_valid().then(|| a.nodes.get(a.root_ref.0)).flatten();
+    let node_b = b.root_ref.is_valid().then(|| b.nodes.get(b.root_ref.0)).flatten();
+
+    diff_nodes(
+        node_a,
+        &a.nodes,
+        &a.values,
+        node_b,
+        &b.nodes,
+        &b.values,
+        &mut path,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn diff_nodes<P, V, H>(
+    node_a: Option<&Node<P, V, H>>,
+    nodes_a: &NodesStorage<P, V, H>,
+    values_a: &ValuesStorage<P, V>,
+    node_b: Option<&Node<P, V, H>>,
+    nodes_b: &NodesStorage<P, V, H>,
+    values_b: &ValuesStorage<P, V>,
+    path: &mut Vec<Nibble>,
+) -> Option<DiffPoint>
+where
+    P: AsRef<[u8]>,
+    V: AsRef<[u8]> + Eq,
+    H: Digest,
+{
+    let mismatch = |a: NodeInfo, b: NodeInfo, path: &[Nibble]| {
+        Some(DiffPoint {
+            path: NibbleVec::from_nibbles(path.iter().copied()),
+            depth: path.len(),
+            a,
+            b,
+        })
+    };
+
+    match (node_a, node_b) {
+        (None, None) => None,
+        (None, Some(node_b)) => mismatch(NodeInfo::Missing, node_kind(node_b), path),
+        (Some(node_a), None) => mismatch(node_kind(node_a), NodeInfo::Missing, path),
+        (Some(Node::Branch(a)), Some(Node::Branch(b))) => {
+            if a.value_ref.is_valid() != b.value_ref.is_valid()
+                || (a.value_ref.is_valid()
+                    && values_a.get(a.value_ref.0).map(|(_, v)| v)
+                        != values_b.get(b.value_ref.0).map(|(_, v)| v))
+            {
+                return mismatch(NodeInfo::Branch, NodeInfo::Branch, path);
+            }
+
+            for (nibble, (choice_a, choice_b)) in a.choices.iter().zip(b.choices.iter()).enumerate() {
+                let child_a = resolve(choice_a, nodes_a);
+                let child_b = resolve(choice_b, nodes_b);
+                if child_a.is_none() && child_b.is_none() {
+                    continue;
+                }
+
+                path.push(Nibble::try_from(nibble as u8).expect("nibble index is always < 16"));
+                let result =
+                    diff_nodes(child_a, nodes_a, values_a, child_b, nodes_b, values_b, path);
+                path.pop();
+
+                if result.is_some() {
+                    return result;
+                }
+            }
+
+            None
+        }
+        (Some(Node::Extension(a)), Some(Node::Extension(b))) => {
+            if a.prefix != b.prefix {
+                return mismatch(NodeInfo::Extension, NodeInfo::Extension, path);
+            }
+
+            path.extend(a.prefix.iter());
+            let result = diff_nodes(
+                nodes_a.get(a.child_ref.0),
+                nodes_a,
+                values_a,
+                nodes_b.get(b.child_ref.0),
+                nodes_b,
+                values_b,
+                path,
+            );
+            path.truncate(path.len() - a.prefix.iter().count());
+
+            result
+        }
+        (Some(Node::Leaf(a)), Some(Node::Leaf(b))) => {
+            let entry_a = values_a.get(a.value_ref.0);
+            let entry_b = values_b.get(b.value_ref.0);
+
+            if entry_a.map(|(k, v)| (k.as_ref(), v)) != entry_b.map(|(k, v)| (k.as_ref(), v)) {
+                return mismatch(NodeInfo::Leaf, NodeInfo::Leaf, path);
+            }
+
+            None
+        }
+        (Some(node_a), Some(node_b)) => mismatch(node_kind(node_a), node_kind(node_b), path),
+    }
+}
+
+fn node_kind<P, V, H>(node: &Node<P, V, H>) -> NodeInfo
+where
+    P: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+    H: Digest,
+{
+    match node {
+        Node::Branch(_) => NodeInfo::Branch,
+        Node::Extension(_) => NodeInfo::Extension,
+        Node::Leaf(_) => NodeInfo::Leaf,
+    }
+}
+
+fn resolve<'a, P, V, H>(
+    node_ref: &NodeRef,
+    nodes: &'a NodesStorage<P, V, H>,
+) -> Option<&'a Node<P, V, H>>
+where
+    P: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+    H: Digest,
+{
+    node_ref.is_valid().then(|| nodes.get(node_ref.0)).flatten()
+}
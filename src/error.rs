@@ -0,0 +1,30 @@
+//! A crate-level error type for storage-backed paths, so a missing or corrupted record in an
+//! external [`crate::backend::NodeBackend`]/[`crate::store::NodeStore`]/
+//! [`crate::value_store::ValueStore`] surfaces to the caller instead of panicking.
+//!
+//! The in-memory tree API stays infallible — every reference it holds is guaranteed to resolve,
+//! since nothing ever leaves the arena. It's only once a `get`/`put`/`del` crosses into a real
+//! backend (disk, network, a separate process) that "the record isn't there" becomes a normal,
+//! expected outcome rather than a bug, which is what this type is for.
+
+use std::fmt;
+
+/// What went wrong resolving a node or value through an external store.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// A hash or key the tree expected to resolve had nothing behind it in the backing store.
+    MissingNode(Vec<u8>),
+    /// The backing store returned bytes that don't decode into a valid node or value.
+    Corruption(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::MissingNode(key) => write!(f, "missing node/value for key {key:02x?}"),
+            Error::Corruption(reason) => write!(f, "corrupted backing record: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
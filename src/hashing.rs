@@ -0,0 +1,197 @@
+//! Cached, per-node hashing with dirty tracking.
+//!
+//! Recomputing a node's hash is only needed when something below it changed. [`NodeHash`] caches
+//! the last computed [`NodeHashRef`] and is invalidated (via [`NodeHash::mark_as_dirty`]) along
+//! the path touched by an insertion or removal; untouched siblings keep serving their cached
+//! value straight out of [`NodeHash::extract_ref`].
+
+use digest::{Digest, Output};
+use std::cell::RefCell;
+
+/// The encoded form of a node: either embedded inline (when short enough) or referenced by hash.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NodeHashRef<H>
+where
+    H: Digest,
+{
+    Inline(Vec<u8>),
+    Hashed(Output<H>),
+}
+
+impl<H> AsRef<[u8]> for NodeHashRef<H>
+where
+    H: Digest,
+{
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::Inline(x) => x,
+            Self::Hashed(x) => x,
+        }
+    }
+}
+
+/// A lazily computed, invalidatable node hash.
+///
+/// Stored behind a `RefCell` so `compute_hash` (which only needs `&self`, since it does not
+/// otherwise mutate the node) can still populate the cache on a cache miss.
+#[derive(Debug, Default)]
+pub struct NodeHash<H>(RefCell<Option<NodeHashRef<H>>>)
+where
+    H: Digest;
+
+impl<H> Clone for NodeHash<H>
+where
+    H: Digest,
+{
+    fn clone(&self) -> Self {
+        Self(RefCell::new(self.0.borrow().clone()))
+    }
+}
+
+impl<H> NodeHash<H>
+where
+    H: Digest,
+{
+    /// Invalidate the cached hash. Must be called on every node along the path mutated by an
+    /// insertion or removal.
+    pub fn mark_as_dirty(&mut self) {
+        *self.0.borrow_mut() = None;
+    }
+
+    /// Return the cached hash, if the node is clean.
+    pub fn extract_ref(&self) -> Option<NodeHashRef<H>> {
+        self.0.borrow().clone()
+    }
+
+    fn store(&self, value: NodeHashRef<H>) {
+        *self.0.borrow_mut() = Some(value);
+    }
+}
+
+/// Accumulates a node's encoded payload and, on [`NodeHasher::finalize`], inlines or hashes it
+/// and writes the result back into the originating [`NodeHash`] cache.
+pub struct NodeHasher<'a, H>
+where
+    H: Digest,
+{
+    hash: &'a NodeHash<H>,
+    buffer: Vec<u8>,
+}
+
+impl<'a, H> NodeHasher<'a, H>
+where
+    H: Digest,
+{
+    pub fn new(hash: &'a NodeHash<H>) -> Self {
+        Self {
+            hash,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Reserve space for a list header of `children_len` payload bytes.
+    pub fn write_list_header(&mut self, children_len: usize) {
+        self.buffer
+            .extend_from_slice(&(children_len as u64).to_be_bytes());
+    }
+
+    /// Write `data` verbatim (used for already-encoded child references).
+    pub fn write_raw(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    /// Write `data` as a length-prefixed byte string.
+    pub fn write_bytes(&mut self, data: &[u8]) {
+        self.buffer
+            .extend_from_slice(&(data.len() as u32).to_be_bytes());
+        self.buffer.extend_from_slice(data);
+    }
+
+    /// The encoded length of a byte string of length `len` whose first byte is `first_byte`,
+    /// without actually writing it (used to size a parent's list header up front).
+    pub fn bytes_len(len: usize, first_byte: u8) -> usize {
+        if len == 1 && first_byte < 0x80 {
+            1
+        } else {
+            1 + len
+        }
+    }
+
+    /// Inline the payload if it's shorter than a digest, otherwise hash it; cache and return the
+    /// result.
+    pub fn finalize(self) -> NodeHashRef<H> {
+        let result = if self.buffer.len() < <H as Digest>::output_size() {
+            NodeHashRef::Inline(self.buffer)
+        } else {
+            NodeHashRef::Hashed(H::digest(&self.buffer))
+        };
+
+        self.hash.store(result.clone());
+        result
+    }
+}
+
+/// Reads back the list header and length-prefixed items written by [`NodeHasher`] — the inverse
+/// of [`NodeHasher::write_list_header`]/[`NodeHasher::write_bytes`], for a caller that has raw
+/// encoded bytes (e.g. loaded from a backend by hash, via [`crate::node_handle::NodeLoader`]) and
+/// wants to recover a node's children without having kept the original node around.
+///
+/// NOTE: an item written with [`NodeHasher::write_raw`] — an *inline* child, i.e. one short enough
+/// that [`NodeHasher::finalize`] embedded it directly instead of hashing it — carries no length
+/// prefix of its own, so [`NodeDecoder::read_bytes`] can't recover its boundary from the encoded
+/// bytes alone; it would have to already be resolved in memory rather than roundtripped through
+/// this format. [`crate::nodes::branch::BranchNode::decode_choices`] documents the same caveat at
+/// its one call site.
+pub struct NodeDecoder<'a> {
+    remaining: &'a [u8],
+}
+
+impl<'a> NodeDecoder<'a> {
+    /// Parse `encoded`'s list header, returning a decoder positioned at the first item.
+    pub fn new(encoded: &'a [u8]) -> Option<Self> {
+        let (header, rest) = encoded.split_at_checked(8)?;
+        let children_len = u64::from_be_bytes(header.try_into().unwrap()) as usize;
+        (rest.len() == children_len).then_some(Self { remaining: rest })
+    }
+
+    /// Read the next length-prefixed byte string, as written by [`NodeHasher::write_bytes`].
+    pub fn read_bytes(&mut self) -> Option<&'a [u8]> {
+        let (len_bytes, rest) = self.remaining.split_at_checked(4)?;
+        let len = u32::from_be_bytes(len_bytes.try_into().unwrap()) as usize;
+        let (data, rest) = rest.split_at_checked(len)?;
+        self.remaining = rest;
+        Some(data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha3::Keccak256;
+
+    #[test]
+    fn decoder_reads_back_what_the_hasher_wrote() {
+        let hash = NodeHash::<Keccak256>::default();
+        let mut hasher = NodeHasher::new(&hash);
+
+        hasher.write_list_header((4 + 4) + (4 + 0) + (4 + 3));
+        hasher.write_bytes(b"abcd");
+        hasher.write_bytes(b"");
+        hasher.write_bytes(b"efg");
+
+        let encoded = match hasher.finalize() {
+            NodeHashRef::Inline(x) => x,
+            NodeHashRef::Hashed(_) => panic!("expected an inline result"),
+        };
+
+        let mut decoder = NodeDecoder::new(&encoded).expect("valid list header");
+        assert_eq!(decoder.read_bytes(), Some(&b"abcd"[..]));
+        assert_eq!(decoder.read_bytes(), Some(&b""[..]));
+        assert_eq!(decoder.read_bytes(), Some(&b"efg"[..]));
+    }
+
+    #[test]
+    fn decoder_rejects_truncated_input() {
+        assert!(NodeDecoder::new(&[0, 0, 0, 0, 0, 0, 0, 1]).is_none());
+    }
+}
@@ -1,78 +1,262 @@
+//! Ordered, lazy iteration over a tree's `(path, value)` pairs.
+//!
+//! [`TreeIterator`] walks the arena directly rather than collecting into a `Vec` up front, so
+//! holding an iterator over a prefix of a huge tree doesn't pay for the rest of it.
+
 use crate::{
-    node::{BranchNode, Node},
-    PatriciaMerkleTree,
+    nibble::{Nibble, NibbleSlice},
+    node::Node,
+    NodeRef, NodesStorage, PatriciaMerkleTree, ValuesStorage,
 };
+use digest::Digest;
+use std::cmp::Ordering;
 
-/// Iterator state (for each node, like a stack).
+/// One entry on [`TreeIterator`]'s explicit stack.
 ///
-/// The `Node<V>` enum can't be used because it doesn't handle special cases such as the
-/// `ExtensionNode`'s child well.
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
-struct NodeState<'a, V> {
-    node: &'a BranchNode<V>,
-    state: usize,
+/// A branch is the only node kind that needs resumable state: once its own value (if any) has
+/// been yielded, `next_choice` tracks which child slot to try descending into next. Extension
+/// nodes never appear here — [`TreeIterator::descend`] walks straight through them to whatever
+/// they eventually lead to, since they never have a value or branching of their own.
+#[derive(Clone, Copy, Debug)]
+enum Frame {
+    Branch {
+        node_ref: NodeRef,
+        value_emitted: bool,
+        next_choice: usize,
+    },
+    Leaf(NodeRef),
 }
 
-pub struct TreeIterator<'a, V> {
-    tree: Option<&'a PatriciaMerkleTree<V>>,
-    state: Vec<NodeState<'a, V>>,
+/// Iterates a tree's entries in ascending key order.
+///
+/// Built by [`PatriciaMerkleTree::iter`].
+pub struct TreeIterator<'a, P, V, H>
+where
+    P: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+    H: Digest,
+{
+    nodes: &'a NodesStorage<P, V, H>,
+    values: &'a ValuesStorage<P, V>,
+    /// The node this iterator was anchored at — the whole tree for [`PatriciaMerkleTree::iter`],
+    /// or a single subtree's root for [`PatriciaMerkleTree::iter_prefix`]. [`Self::seek`]
+    /// restarts its descent from here rather than from the tree's actual root.
+    root: NodeRef,
+    stack: Vec<Frame>,
 }
 
-impl<'a, V> TreeIterator<'a, V> {
-    pub(crate) fn new(tree: &'a PatriciaMerkleTree<V>) -> Self {
-        Self {
-            tree: Some(tree),
-            state: vec![],
+impl<'a, P, V, H> TreeIterator<'a, P, V, H>
+where
+    P: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+    H: Digest,
+{
+    pub(crate) fn new(tree: &'a PatriciaMerkleTree<P, V, H>) -> Self {
+        Self::new_at(tree, tree.root_ref)
+    }
+
+    /// Like [`Self::new`], but anchored at `root` instead of the tree's actual root — what
+    /// [`PatriciaMerkleTree::iter_prefix`] uses to restrict iteration to a single subtree.
+    pub(crate) fn new_at(tree: &'a PatriciaMerkleTree<P, V, H>, root: NodeRef) -> Self {
+        let mut iter = Self {
+            nodes: &tree.nodes,
+            values: &tree.values,
+            root,
+            stack: Vec::new(),
+        };
+        iter.descend(root);
+        iter
+    }
+
+    /// Push the next frame [`Iterator::next`] should resume from for the subtree rooted at
+    /// `node_ref`, walking straight through any chain of extension nodes in the way.
+    fn descend(&mut self, node_ref: NodeRef) {
+        let mut node_ref = node_ref;
+        loop {
+            if !node_ref.is_valid() {
+                return;
+            }
+
+            match self.nodes.get(node_ref.0) {
+                Some(Node::Branch(_)) => {
+                    self.stack.push(Frame::Branch {
+                        node_ref,
+                        value_emitted: false,
+                        next_choice: 0,
+                    });
+                    return;
+                }
+                Some(Node::Extension(extension)) => {
+                    node_ref = extension.child_ref;
+                }
+                Some(Node::Leaf(_)) => {
+                    self.stack.push(Frame::Leaf(node_ref));
+                    return;
+                }
+                None => return,
+            }
+        }
+    }
+
+    /// Reposition the iterator at the first entry `>= key` within its anchored subtree,
+    /// discarding any traversal state it had.
+    ///
+    /// Descends from [`Self::root`], choosing branch slots by `key`'s nibbles and pushing a
+    /// partially-advanced `Frame::Branch` (its `next_choice` set to just past the slot taken,
+    /// rather than `0`) at each level, so resuming iteration after a seek only ever visits
+    /// entries `>= key`. An extension's prefix is compared against `key`'s nibbles at that depth
+    /// to decide whether its whole subtree is skipped, included entirely, or descended into.
+    pub fn seek(&mut self, key: &[u8]) {
+        self.stack.clear();
+        let key_nibbles: Vec<Nibble> = NibbleSlice::new(key).collect();
+        self.seek_from(self.root, &key_nibbles, key, 0);
+    }
+
+    fn seek_from(&mut self, node_ref: NodeRef, key_nibbles: &[Nibble], key_bytes: &[u8], pos: usize) {
+        if !node_ref.is_valid() {
+            return;
+        }
+
+        // `key` is fully consumed: everything at or below `node_ref` shares it as a prefix (or
+        // equals it exactly), so it all sorts `>= key` — descend normally, same as iterating
+        // from scratch.
+        if pos >= key_nibbles.len() {
+            self.descend(node_ref);
+            return;
+        }
+
+        match self.nodes.get(node_ref.0) {
+            Some(Node::Leaf(leaf)) => {
+                let (path, _) = self
+                    .values
+                    .get(leaf.value_ref.0)
+                    .expect("value_ref on a live leaf always points at a live value");
+                if path.as_ref() >= key_bytes {
+                    self.stack.push(Frame::Leaf(node_ref));
+                }
+            }
+            Some(Node::Extension(extension)) => {
+                let prefix: Vec<Nibble> = extension.prefix.iter().collect();
+                let remaining = &key_nibbles[pos..];
+                let common = prefix.len().min(remaining.len());
+
+                match prefix[..common].cmp(&remaining[..common]) {
+                    // This extension's whole subtree shares a prefix less than `key`'s nibbles
+                    // here: every path under it sorts before `key`.
+                    Ordering::Less => {}
+                    // Greater at the first differing nibble: every path under it sorts after
+                    // `key`, so the whole subtree qualifies.
+                    Ordering::Greater => self.descend(node_ref),
+                    Ordering::Equal => {
+                        if remaining.len() <= prefix.len() {
+                            // `key` ends at or before the end of this extension's own prefix:
+                            // it's a prefix of (or equal to) every path under here.
+                            self.descend(node_ref);
+                        } else {
+                            self.seek_from(extension.child_ref, key_nibbles, key_bytes, pos + prefix.len());
+                        }
+                    }
+                }
+            }
+            Some(Node::Branch(branch)) => {
+                let choice = usize::from(key_nibbles[pos]);
+
+                // This branch's own value (if any) sits at a shorter path than `key`, so it
+                // sorts before it; slots before `choice` sort before `key` too. Queue up
+                // everything from `choice + 1` onward, then descend into `choice` itself (pushed
+                // on top, so it's visited first) to find where `key` actually falls within it.
+                self.stack.push(Frame::Branch {
+                    node_ref,
+                    value_emitted: true,
+                    next_choice: choice + 1,
+                });
+
+                let child_ref = branch.choices[choice];
+                if child_ref.is_valid() {
+                    self.seek_from(child_ref, key_nibbles, key_bytes, pos + 1);
+                }
+            }
+            None => {}
         }
     }
 }
 
-impl<'a, V> Iterator for TreeIterator<'a, V> {
-    type Item = (&'a [u8; 32], &'a V);
+impl<'a, P, V, H> Iterator for TreeIterator<'a, P, V, H>
+where
+    P: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+    H: Digest,
+{
+    type Item = (&'a P, &'a V);
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            if let Some(tree) = self.tree.take() {
-                self.state.push(match &tree.root_node {
-                    Some(root_node) => {
-                        let current_node = root_node;
-                        NodeState {
-                            node: match current_node {
-                                Node::Branch(branch_node) => branch_node,
-                                Node::Extension(extension_node) => extension_node.child(),
-                                Node::Leaf(leaf_node) => {
-                                    break Some((leaf_node.key(), leaf_node.value()))
-                                }
-                            },
-                            state: 0,
+            let frame = *self.stack.last()?;
+
+            match frame {
+                Frame::Leaf(node_ref) => {
+                    self.stack.pop();
+
+                    let Some(Node::Leaf(leaf)) = self.nodes.get(node_ref.0) else {
+                        unreachable!("a Frame::Leaf always points at a live Leaf node");
+                    };
+                    let (path, value) = self
+                        .values
+                        .get(leaf.value_ref.0)
+                        .expect("value_ref on a live leaf always points at a live value");
+                    return Some((path, value));
+                }
+                Frame::Branch {
+                    node_ref,
+                    value_emitted,
+                    next_choice,
+                } => {
+                    let Some(Node::Branch(branch)) = self.nodes.get(node_ref.0) else {
+                        unreachable!("a Frame::Branch always points at a live Branch node");
+                    };
+
+                    if !value_emitted {
+                        *self.stack.last_mut().expect("just peeked") = Frame::Branch {
+                            node_ref,
+                            value_emitted: true,
+                            next_choice,
+                        };
+
+                        if branch.value_ref.is_valid() {
+                            let (path, value) = self
+                                .values
+                                .get(branch.value_ref.0)
+                                .expect("value_ref on a live branch always points at a live value");
+                            return Some((path, value));
                         }
+                        continue;
                     }
-                    None => break None,
-                });
-            }
 
-            match self.state.pop() {
-                Some(last_state) if last_state.state < last_state.node.choices().len() => {
-                    self.state.push(NodeState {
-                        node: last_state.node,
-                        state: last_state.state + 1,
-                    });
+                    let mut choice = next_choice;
+                    let mut child_to_descend = None;
+                    while choice < 16 {
+                        let child_ref = branch.choices[choice];
+                        choice += 1;
+                        if child_ref.is_valid() {
+                            child_to_descend = Some(child_ref);
+                            break;
+                        }
+                    }
 
-                    if let Some(choice) = &last_state.node.choices()[last_state.state] {
-                        self.state.push(NodeState {
-                            node: match choice.as_ref() {
-                                Node::Branch(branch_node) => branch_node,
-                                Node::Extension(extension_node) => extension_node.child(),
-                                Node::Leaf(leaf_node) => {
-                                    break Some((leaf_node.key(), leaf_node.value()))
-                                }
-                            },
-                            state: 0,
-                        });
+                    match child_to_descend {
+                        Some(child_ref) => {
+                            *self.stack.last_mut().expect("just peeked") = Frame::Branch {
+                                node_ref,
+                                value_emitted: true,
+                                next_choice: choice,
+                            };
+                            self.descend(child_ref);
+                        }
+                        None => {
+                            self.stack.pop();
+                        }
                     }
                 }
-                None => break None,
-                _ => {}
             }
         }
     }
@@ -80,68 +264,128 @@ impl<'a, V> Iterator for TreeIterator<'a, V> {
 
 #[cfg(test)]
 mod test {
-    use super::*;
-    use crate::{pm_tree, pm_tree_key};
+    use crate::PatriciaMerkleTree;
+    use sha3::Keccak256;
 
     #[test]
-    fn iterate_empty() {
-        let tree = pm_tree!(<()>);
-        assert_eq!(&tree.iter().collect::<Vec<_>>(), &[]);
+    fn iterates_branch_children_in_ascending_nibble_order() {
+        let mut tree = PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
+
+        tree.insert(vec![0x20], vec![0xAA]);
+        tree.insert(vec![0x10], vec![0xBB]);
+        tree.insert(vec![0x30], vec![0xCC]);
+
+        let paths: Vec<Vec<u8>> = tree.iter().map(|(path, _)| path.clone()).collect();
+        assert_eq!(paths, vec![vec![0x10], vec![0x20], vec![0x30]]);
     }
 
     #[test]
-    fn iterate_branches() {
-        let key_a =
-            pm_tree_key!("0000000000000000000000000000000000000000000000000000000000000000");
-        let key_b =
-            pm_tree_key!("1000000000000000000000000000000000000000000000000000000000000000");
-        let key_c =
-            pm_tree_key!("8000000000000000000000000000000000000000000000000000000000000000");
-        let key_d =
-            pm_tree_key!("f000000000000000000000000000000000000000000000000000000000000000");
-
-        let tree = pm_tree! {
-            branch {
-                0x00 => leaf { key_a => 1 },
-                0x01 => leaf { key_b => 2 },
-                0x08 => leaf { key_c => 3 },
-                0x0f => leaf { key_d => 4 },
-            }
-        };
+    fn a_branchs_own_value_is_yielded_before_its_children() {
+        let mut tree = PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
 
-        assert_eq!(
-            &tree.iter().collect::<Vec<_>>(),
-            &[(&key_a, &1), (&key_b, &2), (&key_c, &3), (&key_d, &4)],
-        );
+        // A single top-level byte sharing a prefix with a longer path forces a branch with its
+        // own value (the short path) alongside a child leading to the long one.
+        tree.insert(vec![0x10], vec![0xAA]);
+        tree.insert(vec![0x10, 0x20], vec![0xBB]);
+
+        let paths: Vec<Vec<u8>> = tree.iter().map(|(path, _)| path.clone()).collect();
+        assert_eq!(paths, vec![vec![0x10], vec![0x10, 0x20]]);
     }
 
     #[test]
-    fn iterate_extension() {
-        let key_a =
-            pm_tree_key!("0000000000000000000000000000000000000000000000000000000000000000");
-        let key_b =
-            pm_tree_key!("0001000000000000000000000000000000000000000000000000000000000000");
-
-        let pm_tree = pm_tree! {
-            extension { "000", branch {
-                0 => leaf { key_a => 0 },
-                1 => leaf { key_b => 1 },
-            } }
-        };
+    fn walks_straight_through_an_extension_to_its_child() {
+        let mut tree = PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
 
+        tree.insert(vec![0x12, 0x34], vec![0xAA]);
+        tree.insert(vec![0x12, 0x56], vec![0xBB]);
+
+        let entries: Vec<(Vec<u8>, Vec<u8>)> = tree
+            .iter()
+            .map(|(path, value)| (path.clone(), value.clone()))
+            .collect();
         assert_eq!(
-            &pm_tree.iter().collect::<Vec<_>>(),
-            &[(&key_a, &0), (&key_b, &1)],
+            entries,
+            vec![
+                (vec![0x12, 0x34], vec![0xAA]),
+                (vec![0x12, 0x56], vec![0xBB]),
+            ]
         );
     }
 
     #[test]
-    fn iterate_leaf() {
-        let key = pm_tree_key!("0000000000000000000000000000000000000000000000000000000000000000");
-        let pm_tree = pm_tree! {
-            leaf { key => 42 }
-        };
+    fn yields_nothing_for_an_empty_tree() {
+        let tree = PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
+        assert_eq!(tree.iter().count(), 0);
+    }
+
+    #[test]
+    fn seek_positions_at_the_first_entry_greater_or_equal() {
+        let mut tree = PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
+        tree.insert(vec![0x10], vec![0xAA]);
+        tree.insert(vec![0x20], vec![0xBB]);
+        tree.insert(vec![0x30], vec![0xCC]);
+
+        let mut iter = tree.iter();
+        iter.seek(&[0x15]);
+        let paths: Vec<Vec<u8>> = iter.map(|(path, _)| path.clone()).collect();
+        assert_eq!(paths, vec![vec![0x20], vec![0x30]]);
+    }
+
+    #[test]
+    fn seek_to_an_exact_key_includes_it() {
+        let mut tree = PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
+        tree.insert(vec![0x10], vec![0xAA]);
+        tree.insert(vec![0x20], vec![0xBB]);
+
+        let mut iter = tree.iter();
+        iter.seek(&[0x20]);
+        let paths: Vec<Vec<u8>> = iter.map(|(path, _)| path.clone()).collect();
+        assert_eq!(paths, vec![vec![0x20]]);
+    }
+
+    #[test]
+    fn seek_past_every_key_yields_nothing() {
+        let mut tree = PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
+        tree.insert(vec![0x10], vec![0xAA]);
+        tree.insert(vec![0x20], vec![0xBB]);
+
+        let mut iter = tree.iter();
+        iter.seek(&[0xFF]);
+        assert_eq!(iter.count(), 0);
+    }
+
+    #[test]
+    fn seek_through_an_extension_lands_between_its_children() {
+        let mut tree = PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
+        tree.insert(vec![0x12, 0x34], vec![0xAA]);
+        tree.insert(vec![0x12, 0x56], vec![0xBB]);
+        tree.insert(vec![0x78], vec![0xCC]);
+
+        let mut iter = tree.iter();
+        iter.seek(&[0x12, 0x40]);
+        let paths: Vec<Vec<u8>> = iter.map(|(path, _)| path.clone()).collect();
+        assert_eq!(paths, vec![vec![0x12, 0x56], vec![0x78]]);
+    }
+
+    #[test]
+    fn iter_prefix_restricts_to_the_matching_subtree() {
+        let mut tree = PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
+        tree.insert(vec![0x12, 0x34], vec![0xAA]);
+        tree.insert(vec![0x12, 0x56], vec![0xBB]);
+        tree.insert(vec![0x78], vec![0xCC]);
+
+        let paths: Vec<Vec<u8>> = tree
+            .iter_prefix(&vec![0x12])
+            .map(|(path, _)| path.clone())
+            .collect();
+        assert_eq!(paths, vec![vec![0x12, 0x34], vec![0x12, 0x56]]);
+    }
+
+    #[test]
+    fn iter_prefix_with_no_matches_yields_nothing() {
+        let mut tree = PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
+        tree.insert(vec![0x12, 0x34], vec![0xAA]);
 
-        assert_eq!(&pm_tree.iter().collect::<Vec<_>>(), &[(&key, &42)]);
+        assert_eq!(tree.iter_prefix(&vec![0x99]).count(), 0);
     }
 }
@@ -3,21 +3,36 @@
 #![deny(warnings)]
 
 use self::{nibble::Nibble, node::Node};
-use crate::nodes::LeafNode;
+use crate::nodes::{BranchNode, ExtensionNode, LeafNode};
 use digest::{Digest, Output};
-use nibble::NibbleSlice;
+use nibble::{NibbleSlice, NibbleVec};
 use node::InsertAction;
 use slab::Slab;
 use std::{
-    io::Write,
+    collections::HashMap,
+    io::{Read, Write},
     mem::{replace, size_of},
 };
+use store::NodeStore;
 use util::{DigestBuf, INVALID_REF};
 
+pub mod backend;
+pub mod changeset;
+pub mod diff;
+pub mod error;
+mod hashing;
+pub mod iter;
 pub mod nibble;
 mod node;
+pub mod node_handle;
+pub mod node_hasher;
 mod nodes;
+mod pruner;
+pub mod secure;
+pub mod store;
 mod util;
+pub mod value_store;
+pub mod version;
 
 type NodesStorage<P, V, H> = Slab<Node<P, V, H>>;
 type ValuesStorage<P, V> = Slab<(P, V)>;
@@ -74,24 +89,49 @@ where
 
     /// Return whether the tree is empty.
     pub fn is_empty(&self) -> bool {
-        self.nodes.is_empty()
+        NodeStore::is_empty(&self.nodes)
     }
 
     /// Return the number of values in the tree.
     pub fn len(&self) -> usize {
-        self.values.len()
+        NodeStore::len(&self.values)
     }
 
     /// Retrieve a value from the tree given its path.
     pub fn get(&self, path: &P) -> Option<&V> {
-        self.nodes.get(self.root_ref.0).and_then(|root_node| {
+        NodeStore::get(&self.nodes, self.root_ref.0).and_then(|root_node| {
             root_node.get(&self.nodes, &self.values, NibbleSlice::new(path.as_ref()))
         })
     }
 
+    /// Return whether `path` has a value associated with it, without cloning or borrowing it.
+    pub fn contains(&self, path: &P) -> bool {
+        self.get(path).is_some()
+    }
+
+    /// Iterate over every `(path, value)` pair in the tree, in ascending key order.
+    ///
+    /// This walks the arena lazily rather than collecting into a `Vec` up front, so holding an
+    /// iterator over a prefix of a huge tree doesn't pay for the rest of it.
+    pub fn iter(&self) -> crate::iter::TreeIterator<P, V, H> {
+        crate::iter::TreeIterator::new(self)
+    }
+
+    /// Iterate, in ascending key order, over only the entries whose path starts with `prefix`.
+    ///
+    /// Shares [`Self::node_covering_prefix`]'s descent, so (like [`Self::serialize_subtree`])
+    /// this doesn't materialize anything outside of `prefix`'s own subtree. Returns an empty
+    /// iterator if the tree holds nothing under `prefix`.
+    pub fn iter_prefix(&self, prefix: &P) -> crate::iter::TreeIterator<P, V, H> {
+        match self.node_covering_prefix(prefix.as_ref()) {
+            Some(node_ref) => crate::iter::TreeIterator::new_at(self, node_ref),
+            None => crate::iter::TreeIterator::new_at(self, NodeRef(INVALID_REF)),
+        }
+    }
+
     /// Insert a value into the tree.
     pub fn insert(&mut self, path: P, value: V) -> Option<V> {
-        match self.nodes.try_remove(self.root_ref.0) {
+        match NodeStore::try_remove(&mut self.nodes, self.root_ref.0) {
             Some(root_node) => {
                 // If the tree is not empty, call the root node's insertion logic.
                 let (root_node, insert_action) = root_node.insert(
@@ -99,14 +139,12 @@ where
                     &mut self.values,
                     NibbleSlice::new(path.as_ref()),
                 );
-                self.root_ref = NodeRef(self.nodes.insert(root_node));
+                self.root_ref = NodeRef(NodeStore::insert(&mut self.nodes, root_node));
 
                 match insert_action.quantize_self(self.root_ref) {
                     InsertAction::Insert(node_ref) => {
-                        let value_ref = ValueRef(self.values.insert((path, value)));
-                        match self
-                            .nodes
-                            .get_mut(node_ref.0)
+                        let value_ref = ValueRef(NodeStore::insert(&mut self.values, (path, value)));
+                        match NodeStore::get_mut(&mut self.nodes, node_ref.0)
                             .expect("inconsistent internal tree structure")
                         {
                             Node::Leaf(leaf_node) => leaf_node.update_value_ref(value_ref),
@@ -119,9 +157,7 @@ where
                         None
                     }
                     InsertAction::Replace(value_ref) => {
-                        let (_, old_value) = self
-                            .values
-                            .get_mut(value_ref.0)
+                        let (_, old_value) = NodeStore::get_mut(&mut self.values, value_ref.0)
                             .expect("inconsistent internal tree structure");
 
                         Some(replace(old_value, value))
@@ -131,17 +167,173 @@ where
             }
             None => {
                 // If the tree is empty, just add a leaf.
-                let value_ref = ValueRef(self.values.insert((path, value)));
-                self.root_ref = NodeRef(self.nodes.insert(LeafNode::new(value_ref).into()));
+                let value_ref = ValueRef(NodeStore::insert(&mut self.values, (path, value)));
+                self.root_ref = NodeRef(NodeStore::insert(&mut self.nodes, LeafNode::new(value_ref).into()));
 
                 None
             }
         }
     }
 
+    /// Remove a value from the tree given its path, returning it if it was present.
+    pub fn remove(&mut self, path: &P) -> Option<V> {
+        let root_node = NodeStore::try_remove(&mut self.nodes, self.root_ref.0)?;
+
+        let (root_node, old_value) =
+            root_node.remove(&mut self.nodes, &mut self.values, NibbleSlice::new(path.as_ref()));
+
+        self.root_ref = match root_node {
+            Some(root_node) => NodeRef(NodeStore::insert(&mut self.nodes, root_node)),
+            None => NodeRef(INVALID_REF),
+        };
+
+        old_value
+    }
+
+    /// Remove every entry whose path starts with `prefix`, returning the removed `(path, value)`
+    /// pairs.
+    ///
+    /// Unlike calling [`Self::remove`] once per key in the tree, this skips a whole subtree as
+    /// soon as an extension node's own prefix is found to diverge from `prefix` — the nodes
+    /// visited are bounded by what actually lies on the way to (or within) the matching subtree,
+    /// not by the tree's total leaf count.
+    pub fn drain_prefix(&mut self, prefix: &[u8]) -> Vec<(P, V)>
+    where
+        P: Clone,
+    {
+        let target: Vec<Nibble> = NibbleSlice::new(prefix).collect();
+
+        let mut paths = Vec::new();
+        collect_prefixed_paths(&self.nodes, &self.values, self.root_ref, &target, 0, false, &mut paths);
+
+        paths
+            .into_iter()
+            .filter_map(|path| {
+                let value = self.remove(&path)?;
+                Some((path, value))
+            })
+            .collect()
+    }
+
+    /// Apply a batch of upserts and deletions, computing the root hash once at the end instead of
+    /// after every individual `insert`/`remove`.
+    ///
+    /// `batch` should already be sorted by path, following jmt's `put_value_sets` contract.
+    /// Unlike that design, this does not yet share a single top-down traversal across entries —
+    /// each one still walks the tree independently via [`Self::insert`]/[`Self::remove`]; per-node
+    /// hashes are already cached and only recomputed lazily on [`Self::compute_hash`], so what
+    /// this mainly buys the caller is not having to remember to defer that call themselves.
+    /// Returns the new root hash and the values freed by any deletions in the batch.
+    pub fn apply_batch(&mut self, batch: impl IntoIterator<Item = (P, Option<V>)>) -> (Output<H>, Vec<V>) {
+        let mut removed = Vec::new();
+
+        for (path, value) in batch {
+            match value {
+                Some(value) => {
+                    self.insert(path, value);
+                }
+                None => {
+                    if let Some(old_value) = self.remove(&path) {
+                        removed.push(old_value);
+                    }
+                }
+            }
+        }
+
+        (self.compute_hash().unwrap_or_default(), removed)
+    }
+
+    /// Build a Merkle proof for `path`.
+    ///
+    /// Returns the encoded form of every node visited while resolving `path`, in root-to-leaf
+    /// order. The proof can be checked against a root hash with [`verify_proof`] without needing
+    /// access to the rest of the tree. See [`Self::get_exclusion_proof`] for proving a key is
+    /// absent instead.
+    pub fn get_proof(&mut self, path: &P) -> Option<Proof> {
+        let mut proof = Vec::new();
+
+        let root_node = self.nodes.try_remove(self.root_ref.0)?;
+        let mut root_node = root_node;
+        root_node.get_proof(
+            &mut self.nodes,
+            &self.values,
+            NibbleSlice::new(path.as_ref()),
+            &mut proof,
+        );
+        self.root_ref = NodeRef(self.nodes.insert(root_node));
+
+        Some(Proof(proof))
+    }
+
+    /// Build a proof that `path` has no value under the tree's current root.
+    ///
+    /// Returns `None` if `path` is actually present (use [`Self::get_proof`] for that case
+    /// instead) or if the tree is empty. The proof is the same node chain [`Self::get_proof`]
+    /// would collect, terminating where the search diverges from `path`; check it with
+    /// [`verify_exclusion_proof`].
+    pub fn get_exclusion_proof(&mut self, path: &P) -> Option<Proof> {
+        if self.contains(path) {
+            return None;
+        }
+
+        self.get_proof(path)
+    }
+
+    /// Build a single proof covering several paths at once.
+    ///
+    /// Nodes shared between the individual paths (e.g. everything near the root) are only
+    /// emitted once, which is the whole point of batching: naively concatenating per-key proofs
+    /// in a tree of height `h` costs `k * h` nodes, while a deduplicated batch only needs the
+    /// union of the distinct nodes actually visited.
+    pub fn get_batch_proof(&mut self, paths: &[P]) -> BatchProof {
+        let mut nodes: Vec<Vec<u8>> = Vec::new();
+        let mut entries = Vec::with_capacity(paths.len());
+
+        for path in paths {
+            let Some(proof) = self.get_proof(path) else {
+                continue;
+            };
+
+            let mut indices = Vec::with_capacity(proof.0.len());
+            for node in proof.0 {
+                // Nodes are deduplicated by their encoded bytes: identical encodings always
+                // represent the same node, since the encoding is exactly what gets hashed.
+                let index = match nodes.iter().position(|n| n == &node) {
+                    Some(index) => index,
+                    None => {
+                        nodes.push(node);
+                        nodes.len() - 1
+                    }
+                };
+                indices.push(index);
+            }
+
+            entries.push(indices);
+        }
+
+        BatchProof { nodes, entries }
+    }
+
+    /// Build a [`StructuredProof`] for `path`.
+    ///
+    /// The node-encoding chain is exactly what [`Self::get_proof`] returns; this additionally
+    /// annotates each entry with the [`ProofStep`] that produced it — which branch index was
+    /// followed, or how many nibbles an extension's own prefix consumed — so a verifier can check
+    /// the proof's shape (e.g. "this key resolves through exactly one branch, then a leaf")
+    /// without re-decoding the node encodings itself.
+    pub fn get_structured_proof(&mut self, path: &P) -> Option<StructuredProof> {
+        let Proof(encodings) = self.get_proof(path)?;
+
+        let target: Vec<Nibble> = NibbleSlice::new(path.as_ref()).collect();
+        let mut steps = Vec::new();
+        collect_proof_steps(&self.nodes, self.root_ref, &target, 0, &mut steps);
+
+        Some(StructuredProof(steps.into_iter().zip(encodings).collect()))
+    }
+
     /// Return the root hash of the tree (or recompute if needed).
     pub fn compute_hash(&mut self) -> Option<Output<H>> {
-        self.nodes.try_remove(self.root_ref.0).map(|mut root_node| {
+        NodeStore::try_remove(&mut self.nodes, self.root_ref.0).map(|mut root_node| {
             // TODO: Test what happens when the root node's hash encoding is hashed (len == 32).
             //   Double hash? Or forward the first one?
             let mut hasher = DigestBuf::<H>::new();
@@ -150,21 +342,370 @@ where
                 .unwrap();
             let output = hasher.finalize();
 
-            self.root_ref = NodeRef(self.nodes.insert(root_node));
+            self.root_ref = NodeRef(NodeStore::insert(&mut self.nodes, root_node));
             output
         })
     }
 
     /// Calculate approximated memory usage (both used and allocated).
     pub fn memory_usage(&self) -> (usize, usize) {
-        let mem_consumed = size_of::<Node<P, V, H>>() * self.nodes.len()
-            + size_of::<(P, Output<H>, V)>() * self.values.len();
-        let mem_reserved = size_of::<Node<P, V, H>>() * self.nodes.capacity()
-            + size_of::<(P, Output<H>, V)>() * self.values.capacity();
+        let mem_consumed = size_of::<Node<P, V, H>>() * NodeStore::len(&self.nodes)
+            + size_of::<(P, Output<H>, V)>() * NodeStore::len(&self.values);
+        let mem_reserved = size_of::<Node<P, V, H>>() * NodeStore::capacity(&self.nodes)
+            + size_of::<(P, Output<H>, V)>() * NodeStore::capacity(&self.values);
 
         (mem_consumed, mem_reserved)
     }
 
+    /// Write the whole tree to `w` so it can be [`Self::deserialize`]d later without replaying
+    /// every `insert`.
+    ///
+    /// The arena's raw `usize` keys are an artifact of insertion/removal order, not something
+    /// worth preserving bit-for-bit; instead every occupied node/value slot is renumbered to a
+    /// dense, iteration-order index before being written, and cross-references (`NodeRef`,
+    /// `ValueRef`) are translated to match. `deserialize` reconstructs the arena by inserting in
+    /// that same order into fresh, empty slabs, which is guaranteed to hand back the same dense
+    /// indices.
+    ///
+    /// The header is a version byte (currently always `1`) followed by a one-byte hasher
+    /// identity (`H`'s output size) so a snapshot taken with a different hasher is rejected
+    /// instead of silently producing a tree with the wrong root hash.
+    pub fn serialize<W: Write>(&self, mut w: W) -> std::io::Result<()> {
+        w.write_all(&[1u8])?;
+        w.write_all(&[H::output_size() as u8])?;
+
+        let node_order: Vec<usize> = self.nodes.iter().map(|(key, _)| key).collect();
+        let node_index: HashMap<usize, u64> = node_order
+            .iter()
+            .enumerate()
+            .map(|(i, &key)| (key, i as u64))
+            .collect();
+
+        let value_order: Vec<usize> = self.values.iter().map(|(key, _)| key).collect();
+        let value_index: HashMap<usize, u64> = value_order
+            .iter()
+            .enumerate()
+            .map(|(i, &key)| (key, i as u64))
+            .collect();
+
+        let root_index = node_index
+            .get(&self.root_ref.0)
+            .copied()
+            .unwrap_or(u64::MAX);
+        w.write_all(&root_index.to_be_bytes())?;
+
+        w.write_all(&(value_order.len() as u64).to_be_bytes())?;
+        for key in value_order {
+            let (path, value) = self
+                .values
+                .get(key)
+                .expect("key was just read from this slab's own iteration");
+            write_framed(&mut w, path.as_ref())?;
+            write_framed(&mut w, value.as_ref())?;
+        }
+
+        w.write_all(&(node_order.len() as u64).to_be_bytes())?;
+        for key in node_order {
+            let node = self
+                .nodes
+                .get(key)
+                .expect("key was just read from this slab's own iteration");
+
+            match node {
+                Node::Branch(branch) => {
+                    w.write_all(&[0u8])?;
+                    for child in &branch.choices {
+                        let index = node_index.get(&child.0).copied().unwrap_or(u64::MAX);
+                        w.write_all(&index.to_be_bytes())?;
+                    }
+                    let value_index = value_index
+                        .get(&branch.value_ref.0)
+                        .copied()
+                        .unwrap_or(u64::MAX);
+                    w.write_all(&value_index.to_be_bytes())?;
+                }
+                Node::Extension(extension) => {
+                    w.write_all(&[1u8])?;
+                    write_framed(&mut w, &extension.prefix.encode_compact(false))?;
+                    let child_index = node_index
+                        .get(&extension.child_ref.0)
+                        .copied()
+                        .unwrap_or(u64::MAX);
+                    w.write_all(&child_index.to_be_bytes())?;
+                }
+                Node::Leaf(leaf) => {
+                    w.write_all(&[2u8])?;
+                    let value_index = value_index
+                        .get(&leaf.value_ref.0)
+                        .copied()
+                        .unwrap_or(u64::MAX);
+                    w.write_all(&value_index.to_be_bytes())?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rebuild a tree previously written with [`Self::serialize`].
+    ///
+    /// Returns an error if the header's hasher identity doesn't match `H`, or if the stream is
+    /// truncated or otherwise malformed.
+    pub fn deserialize<R: Read>(mut r: R) -> std::io::Result<Self>
+    where
+        P: From<Vec<u8>>,
+        V: From<Vec<u8>>,
+    {
+        let mut byte = [0u8; 1];
+
+        r.read_exact(&mut byte)?;
+        let _version = byte[0];
+
+        r.read_exact(&mut byte)?;
+        if byte[0] as usize != H::output_size() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "snapshot was written with a different hasher",
+            ));
+        }
+
+        let root_index = read_u64(&mut r)?;
+
+        let value_count = read_u64(&mut r)?;
+        let mut values: ValuesStorage<P, V> = Slab::with_capacity(value_count as usize);
+        for _ in 0..value_count {
+            let path = read_framed(&mut r)?;
+            let value = read_framed(&mut r)?;
+            values.insert((P::from(path), V::from(value)));
+        }
+
+        let node_count = read_u64(&mut r)?;
+        let mut nodes: NodesStorage<P, V, H> = Slab::with_capacity(node_count as usize);
+        for _ in 0..node_count {
+            r.read_exact(&mut byte)?;
+            let node = match byte[0] {
+                0 => {
+                    let mut choices = [NodeRef::default(); 16];
+                    for choice in &mut choices {
+                        *choice = node_ref_from_index(read_u64(&mut r)?);
+                    }
+                    let mut branch = BranchNode::new(choices);
+                    branch.update_value_ref(value_ref_from_index(read_u64(&mut r)?));
+                    Node::Branch(branch)
+                }
+                1 => {
+                    let (prefix, _is_leaf) = NibbleVec::decode_compact(&read_framed(&mut r)?);
+                    let child_ref = node_ref_from_index(read_u64(&mut r)?);
+                    Node::Extension(ExtensionNode::new(prefix, child_ref))
+                }
+                2 => Node::Leaf(LeafNode::new(value_ref_from_index(read_u64(&mut r)?))),
+                _ => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        "unknown node tag",
+                    ))
+                }
+            };
+
+            // A fresh `Slab` with nothing removed always hands back sequential keys, so
+            // inserting in file order reproduces the dense indices `serialize` wrote refs
+            // against.
+            nodes.insert(node);
+        }
+
+        Ok(Self {
+            root_ref: node_ref_from_index(root_index),
+            nodes,
+            values,
+        })
+    }
+
+    /// A more compact on-disk form than [`Self::serialize`], trading that format's O(1)
+    /// arena-index lookups for an O(depth) walk on reload.
+    ///
+    /// [`Self::serialize`] dumps every arena slot as a dense table, spending a `u64` on each of
+    /// a branch's 16 child slots even though most tries leave the bulk of them empty. This walks
+    /// the tree in pre-order instead, via an explicit stack of `NodeRef`s rather than a dense
+    /// slot dump, writing only a tag byte per node plus, for branches, a 16-bit occupied-child
+    /// bitmap. Leaf and branch values are appended to a side buffer in visit order instead of
+    /// being looked up by a translated index.
+    pub fn serialize_compact(&self) -> (Vec<u8>, Vec<u8>) {
+        self.serialize_subtree_from(self.root_ref)
+    }
+
+    /// Serialize only the subtree covering every path starting with `prefix`, in the same format
+    /// [`Self::serialize_compact`] uses (so [`Self::deserialize_compact`] reads it back unchanged).
+    ///
+    /// Returns `None` if the tree holds nothing under `prefix`. Like [`Self::drain_prefix`], this
+    /// doesn't require `prefix` to resolve to an existing value — only that some node's subtree
+    /// covers it — but unlike it, it leaves the tree itself untouched.
+    pub fn serialize_subtree(&self, prefix: &P) -> Option<(Vec<u8>, Vec<u8>)> {
+        let node_ref = self.node_covering_prefix(prefix.as_ref())?;
+        Some(self.serialize_subtree_from(node_ref))
+    }
+
+    /// Descend from the root to the node whose subtree covers every path starting with `prefix`.
+    ///
+    /// Shares [`collect_prefixed_paths`]'s extension-prefix-divergence bailout, for the same
+    /// reason: nothing below a diverging extension can start with `prefix`, so there is no
+    /// covering node to return.
+    fn node_covering_prefix(&self, prefix: &[u8]) -> Option<NodeRef> {
+        let target: Vec<Nibble> = NibbleSlice::new(prefix).collect();
+        let mut node_ref = self.root_ref;
+        let mut pos = 0usize;
+
+        loop {
+            if !node_ref.is_valid() {
+                return None;
+            }
+            if pos >= target.len() {
+                return Some(node_ref);
+            }
+
+            match self.nodes.get(*node_ref)? {
+                Node::Branch(branch) => {
+                    let choice = target[pos];
+                    node_ref = branch.choices[usize::from(choice)];
+                    pos += 1;
+                }
+                Node::Extension(extension) => {
+                    let ext_nibbles: Vec<Nibble> = extension.prefix.iter().collect();
+                    let remaining = &target[pos..];
+                    let common = ext_nibbles
+                        .iter()
+                        .zip(remaining.iter())
+                        .take_while(|(a, b)| a == b)
+                        .count();
+
+                    if common < ext_nibbles.len() && common < remaining.len() {
+                        return None;
+                    }
+
+                    if ext_nibbles.len() <= remaining.len() {
+                        pos += ext_nibbles.len();
+                        node_ref = extension.child_ref;
+                    } else {
+                        // `prefix` is fully consumed partway through this extension's own prefix:
+                        // this extension's whole subtree shares it, so it's the covering node.
+                        return Some(node_ref);
+                    }
+                }
+                Node::Leaf(leaf) => {
+                    let (path, _) = self
+                        .values
+                        .get(*leaf.value_ref)
+                        .expect("value_ref on a live leaf always points at a live value");
+                    let remaining = &target[pos..];
+                    let matches = NibbleSlice::new(path.as_ref())
+                        .skip(pos)
+                        .zip(remaining.iter().copied())
+                        .filter(|(a, b)| a == b)
+                        .count()
+                        == remaining.len();
+
+                    return matches.then_some(node_ref);
+                }
+            }
+        }
+    }
+
+    /// The shared pre-order walk [`Self::serialize_compact`] and [`Self::serialize_subtree`] both
+    /// use, starting from an arbitrary `node_ref` instead of always the tree's own root.
+    fn serialize_subtree_from(&self, node_ref: NodeRef) -> (Vec<u8>, Vec<u8>) {
+        let mut tree = Vec::new();
+        let mut values = Vec::new();
+        let mut stack = vec![node_ref];
+
+        while let Some(node_ref) = stack.pop() {
+            if !node_ref.is_valid() {
+                continue;
+            }
+            let node = self
+                .nodes
+                .get(*node_ref)
+                .expect("node_ref popped from the stack always points at a live node");
+
+            match node {
+                Node::Branch(branch) => {
+                    tree.push(0u8);
+
+                    let mut bitmap: u16 = 0;
+                    for (index, child_ref) in branch.choices.iter().enumerate() {
+                        if child_ref.is_valid() {
+                            bitmap |= 1 << index;
+                        }
+                    }
+                    tree.extend_from_slice(&bitmap.to_be_bytes());
+
+                    tree.push(branch.value_ref.is_valid() as u8);
+                    if branch.value_ref.is_valid() {
+                        let (path, value) = self
+                            .values
+                            .get(*branch.value_ref)
+                            .expect("value_ref on a live branch always points at a live value");
+                        write_framed(&mut values, path.as_ref()).unwrap();
+                        write_framed(&mut values, value.as_ref()).unwrap();
+                    }
+
+                    for child_ref in branch.choices.iter().rev() {
+                        if child_ref.is_valid() {
+                            stack.push(*child_ref);
+                        }
+                    }
+                }
+                Node::Extension(extension) => {
+                    tree.push(1u8);
+                    write_framed(&mut tree, &extension.prefix.encode_compact(false)).unwrap();
+                    stack.push(extension.child_ref);
+                }
+                Node::Leaf(leaf) => {
+                    tree.push(2u8);
+                    let (path, value) = self
+                        .values
+                        .get(*leaf.value_ref)
+                        .expect("value_ref on a live leaf always points at a live value");
+                    write_framed(&mut values, path.as_ref()).unwrap();
+                    write_framed(&mut values, value.as_ref()).unwrap();
+                }
+            }
+        }
+
+        (tree, values)
+    }
+
+    /// Rebuild a tree previously written with [`Self::serialize_compact`].
+    pub fn deserialize_compact(tree: &[u8], values: &[u8]) -> std::io::Result<Self>
+    where
+        P: From<Vec<u8>>,
+        V: From<Vec<u8>>,
+    {
+        let mut nodes = NodesStorage::<P, V, H>::new();
+        let mut values_storage = ValuesStorage::<P, V>::new();
+
+        if tree.is_empty() {
+            return Ok(Self {
+                root_ref: NodeRef::default(),
+                nodes,
+                values: values_storage,
+            });
+        }
+
+        let mut tree_cursor = tree;
+        let mut values_cursor = values;
+        let root_ref = decode_compact_node(
+            &mut tree_cursor,
+            &mut values_cursor,
+            &mut nodes,
+            &mut values_storage,
+        )?;
+
+        Ok(Self {
+            root_ref,
+            nodes,
+            values: values_storage,
+        })
+    }
+
     /// Use after a `.clone()` to reserve the capacity the slabs would have if they hadn't been
     /// cloned.
     ///
@@ -178,6 +719,560 @@ where
     }
 }
 
+/// An ordered sequence of node encodings from root to the terminal node, as produced by
+/// [`PatriciaMerkleTree::get_proof`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Proof(pub Vec<Vec<u8>>);
+
+/// One step of a [`StructuredProof`], recording which kind of node contributed the corresponding
+/// encoded entry and how much of the search path it consumed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProofStep {
+    /// A branch node. `choice` is the nibble (0..=15) the search descended into next, or `None`
+    /// if the search ended at this branch's own value (an empty `path`).
+    Branch { choice: Option<u8> },
+    /// An extension node; `prefix_len` is the length, in nibbles, of its own shared prefix.
+    Extension { prefix_len: usize },
+    /// A leaf node, always the last step of a proof that reaches one.
+    Leaf,
+}
+
+/// A [`Proof`] annotated with per-step structural metadata, as produced by
+/// [`PatriciaMerkleTree::get_structured_proof`].
+///
+/// Unlike the plain [`Proof`], which only carries raw node encodings, this also records what kind
+/// of node produced each entry and how the search path was consumed at that point, in root-to-
+/// terminal order matching [`Self::to_proof`]'s byte chain step for step.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StructuredProof(pub Vec<(ProofStep, Vec<u8>)>);
+
+impl StructuredProof {
+    /// Drop the structural metadata, leaving the plain node-encoding chain [`verify_proof`] (and
+    /// the rest of the flat-proof API) checks.
+    pub fn to_proof(&self) -> Proof {
+        Proof(self.0.iter().map(|(_, bytes)| bytes.clone()).collect())
+    }
+}
+
+/// Verify a [`StructuredProof`] produced by [`PatriciaMerkleTree::get_structured_proof`] against a
+/// known root hash.
+///
+/// Checks the same hash chain [`verify_proof`] does — which, as of its own decode-based check,
+/// already confirms every branch entry's declared choice both matches `path`'s nibble at that
+/// depth and actually leads to the next proof entry — plus that the recorded [`ProofStep`]s
+/// themselves form a path consistent with `path`: each `Branch` step's `choice` must be the
+/// nibble `path` actually supplies at that depth (`None` only once `path` is exhausted).
+///
+/// Extension steps only advance the depth at which later `Branch` choices are compared (clamped
+/// to `path`'s length); their prefix length and their encoded prefix bytes are not checked
+/// against `path`, and a [`ProofStep::Leaf`] step is not checked at all. [`verify_chain`]'s doc
+/// comment explains why (no decoder exists for `ExtensionNode`'s `encode_path`-based encoding).
+pub fn verify_structured<H>(root: &Output<H>, path: &[u8], value: &[u8], proof: &StructuredProof) -> bool
+where
+    H: Digest,
+{
+    let flat = proof.to_proof();
+    if !verify_proof::<H>(root, path, value, &flat) {
+        return false;
+    }
+
+    let target: Vec<Nibble> = NibbleSlice::new(path).collect();
+    let mut pos = 0usize;
+
+    for (step, _) in &proof.0 {
+        match *step {
+            ProofStep::Branch { choice } => {
+                let expected = target.get(pos).copied().map(u8::from);
+                if choice != expected {
+                    return false;
+                }
+                if choice.is_some() {
+                    pos += 1;
+                }
+            }
+            ProofStep::Extension { prefix_len } => {
+                // An extension step's own prefix may run past what's left of `path` — that's
+                // exactly what a valid exclusion proof through a too-short key looks like — so
+                // this only advances `pos`, it never fails the proof on its own.
+                pos = (pos + prefix_len).min(target.len());
+            }
+            ProofStep::Leaf => {}
+        }
+    }
+
+    true
+}
+
+/// A deduplicated proof covering several paths, as produced by
+/// [`PatriciaMerkleTree::get_batch_proof`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BatchProof {
+    /// The distinct node encodings touched by any of the proven paths.
+    nodes: Vec<Vec<u8>>,
+    /// For each proven path, the root-to-leaf sequence of indices into `nodes`.
+    entries: Vec<Vec<usize>>,
+}
+
+/// Verify a [`BatchProof`] produced by [`PatriciaMerkleTree::get_batch_proof`] against a known
+/// root hash. `entries` must list the expected value of each proven path, in the same order the
+/// paths were passed to `get_batch_proof`.
+pub fn verify_batch_proof<H>(root: &Output<H>, entries: &[&[u8]], proof: &BatchProof) -> bool
+where
+    H: Digest,
+{
+    if entries.len() != proof.entries.len() {
+        return false;
+    }
+
+    entries.iter().zip(&proof.entries).all(|(value, indices)| {
+        let nodes = indices
+            .iter()
+            .filter_map(|&index| proof.nodes.get(index).cloned())
+            .collect::<Vec<_>>();
+
+        verify_proof::<H>(root, &[], value, &Proof(nodes))
+    })
+}
+
+/// Verify a proof produced by [`PatriciaMerkleTree::get_proof`] against a known root hash.
+///
+/// `path` identifies the key the proof was built for; `value` is the expected value. The first
+/// proof entry must hash (or, if short enough, equal) to `root`. Each subsequent entry must in
+/// turn be embedded (inline or by hash) within the previous one, which is what ties the chain
+/// together without requiring the rest of the tree — and, unlike that embedding check alone,
+/// [`verify_chain`] also decodes every branch entry it can and confirms the choice it recorded at
+/// that depth is the nibble `path` actually supplies there, so a genuine proof built for a
+/// different key is rejected even when its terminal value happens to match.
+pub fn verify_proof<H>(root: &Output<H>, path: &[u8], value: &[u8], proof: &Proof) -> bool
+where
+    H: Digest,
+{
+    let Some(last) = verify_chain::<H>(root, Some(path), proof) else {
+        return false;
+    };
+
+    if value.is_empty() {
+        return last.is_empty();
+    }
+
+    last.windows(value.len()).any(|window| window == value)
+}
+
+/// Verify an exclusion proof produced by [`PatriciaMerkleTree::get_exclusion_proof`] against a
+/// known root hash.
+///
+/// Unlike [`verify_proof`], there is no expected value to find: it is enough that the chain is a
+/// genuine, unbroken path down from `root`, since [`PatriciaMerkleTree::get_exclusion_proof`]
+/// only ever returns `Some` when the path is confirmed absent at proof-generation time.
+pub fn verify_exclusion_proof<H>(root: &Output<H>, proof: &Proof) -> bool
+where
+    H: Digest,
+{
+    verify_chain::<H>(root, None, proof).is_some()
+}
+
+/// Richer outcome of [`verify_proof_result`], distinguishing a confirmed absence from a chain
+/// that simply doesn't check out.
+///
+/// [`verify_proof`] and [`verify_exclusion_proof`] each collapse this down to the single bit of
+/// information their caller already knows which case they're in; this is for callers that don't
+/// — e.g. a verifier handed a proof without being told up front whether it proves inclusion or
+/// exclusion.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum VerifyResult<'a> {
+    /// The chain is valid and its terminal entry embeds `value`.
+    Present(&'a [u8]),
+    /// The chain is valid but its terminal entry does not embed `value` — a valid exclusion proof.
+    Absent,
+    /// The chain doesn't hash back to `root`, or is empty.
+    Invalid,
+}
+
+/// Verify a proof against `root` and classify the result, rather than just returning whether
+/// `value` was found.
+///
+/// Built on the same [`verify_chain`] plumbing as [`verify_proof`]; use this instead when the
+/// caller can't already tell inclusion and exclusion proofs apart before checking them.
+pub fn verify_proof_result<'a, H>(root: &Output<H>, value: &'a [u8], proof: &Proof) -> VerifyResult<'a>
+where
+    H: Digest,
+{
+    let Some(last) = verify_chain::<H>(root, None, proof) else {
+        return VerifyResult::Invalid;
+    };
+
+    let found = if value.is_empty() {
+        last.is_empty()
+    } else {
+        last.windows(value.len()).any(|window| window == value)
+    };
+
+    if found {
+        VerifyResult::Present(value)
+    } else {
+        VerifyResult::Absent
+    }
+}
+
+/// Replay a proof's hash chain from `root` down to its last entry, returning that last entry's
+/// bytes if every link holds.
+///
+/// When `path` is given, this also decodes every entry it can as a [`BranchNode`] (via
+/// [`BranchNode::decode_choices`]) and confirms the choice recorded there is the nibble `path`
+/// actually supplies at that depth, and that the slot's recorded child is the very next proof
+/// entry — not just that *some* byte string equal to it appears somewhere in the previous entry,
+/// which is all the hash-chain check below guarantees on its own. Without this, a hash chain built
+/// for one key would verify equally well as "proof" for any other key whose value happens to
+/// embed the same way in the terminal node.
+///
+/// Entries that aren't decodable as a branch (extensions and leaves) aren't structurally checked
+/// against `path` here: [`ExtensionNode`]/[`LeafNode`] encode their own prefix/key through
+/// `encode_path`/`DigestBuf`, which — unlike [`BranchNode`]'s [`crate::hashing::NodeHasher`]-based
+/// encoding — has no matching decoder in this crate (see [`crate::changeset`]'s own NOTE on the
+/// same gap). They still have to satisfy the hash-chain check above, which is what a proof
+/// generated for a different key diverging below a shared branch would fail anyway.
+fn verify_chain<'a, H>(root: &Output<H>, path: Option<&[u8]>, proof: &'a Proof) -> Option<&'a [u8]>
+where
+    H: Digest,
+{
+    let proof = &proof.0;
+    let (first, rest) = proof.split_first()?;
+
+    let mut hasher = DigestBuf::<H>::new();
+    hasher.write_all(first).unwrap();
+    if &hasher.finalize() != root && first.as_slice() != root.as_slice() {
+        return None;
+    }
+
+    let target: Vec<Nibble> = path
+        .map(|path| NibbleSlice::new(path).collect())
+        .unwrap_or_default();
+    let mut pos = 0usize;
+    // Once an entry fails to decode as a branch (an extension or a leaf), its own nibble
+    // consumption is unknown, so `pos` can no longer be trusted as an index into `target` for any
+    // entry after it — structural checking stops there rather than comparing against a stale
+    // position. This is the gap documented above.
+    let mut tracking = true;
+
+    let mut previous = first;
+    for next in rest {
+        let mut hasher = DigestBuf::<H>::new();
+        hasher.write_all(next).unwrap();
+        let next_hash = hasher.finalize();
+
+        let embedded_by_hash = previous
+            .windows(next_hash.len())
+            .any(|window| window == next_hash.as_slice());
+        let embedded_inline = previous
+            .windows(next.len().min(previous.len()))
+            .any(|window| window == next.as_slice());
+
+        if !embedded_by_hash && !embedded_inline {
+            return None;
+        }
+
+        if tracking && pos < target.len() {
+            match BranchNode::<Vec<u8>, Vec<u8>, H>::decode_choices(previous) {
+                Some((choices, _value)) => {
+                    let choice = usize::from(target[pos]);
+                    let chose_next = match &choices[choice] {
+                        Some(hash) => {
+                            hash.as_slice() == next_hash.as_slice() || hash.as_slice() == next.as_slice()
+                        }
+                        None => false,
+                    };
+
+                    if !chose_next {
+                        return None;
+                    }
+
+                    pos += 1;
+                }
+                None => tracking = false,
+            }
+        }
+
+        previous = next;
+    }
+
+    Some(previous)
+}
+
+/// Collect the (cloned) paths of every value reachable under `node_ref` whose key starts with
+/// `target`, for [`PatriciaMerkleTree::drain_prefix`].
+///
+/// `matched` is `true` once `target` has already been fully consumed by the nibbles visited so
+/// far — from that point on every value under `node_ref` qualifies, so the whole subtree is
+/// collected without further comparisons. While `matched` is still `false`, an extension node
+/// whose own prefix diverges from the unconsumed part of `target` is skipped entirely: nothing
+/// beneath it can match, so none of its leaves need visiting.
+fn collect_prefixed_paths<P, V, H>(
+    nodes: &NodesStorage<P, V, H>,
+    values: &ValuesStorage<P, V>,
+    node_ref: NodeRef,
+    target: &[Nibble],
+    pos: usize,
+    matched: bool,
+    out: &mut Vec<P>,
+) where
+    P: AsRef<[u8]> + Clone,
+    V: AsRef<[u8]>,
+    H: Digest,
+{
+    if !node_ref.is_valid() {
+        return;
+    }
+
+    let remaining = &target[pos..];
+
+    match nodes.get(node_ref.0) {
+        Some(Node::Branch(branch)) => {
+            if matched || remaining.is_empty() {
+                if branch.value_ref.is_valid() {
+                    let (path, _) = values
+                        .get(branch.value_ref.0)
+                        .expect("inconsistent internal tree structure");
+                    out.push(path.clone());
+                }
+                for &child_ref in branch.choices.iter() {
+                    collect_prefixed_paths(nodes, values, child_ref, target, target.len(), true, out);
+                }
+            } else {
+                let choice = remaining[0];
+                collect_prefixed_paths(
+                    nodes,
+                    values,
+                    branch.choices[usize::from(choice)],
+                    target,
+                    pos + 1,
+                    false,
+                    out,
+                );
+            }
+        }
+        Some(Node::Extension(extension)) => {
+            if matched {
+                collect_prefixed_paths(nodes, values, extension.child_ref, target, target.len(), true, out);
+            } else {
+                let ext_nibbles: Vec<Nibble> = extension.prefix.iter().collect();
+                let common = ext_nibbles
+                    .iter()
+                    .zip(remaining.iter())
+                    .take_while(|(a, b)| *a == b)
+                    .count();
+
+                if common < ext_nibbles.len() && common < remaining.len() {
+                    // The extension's own prefix diverges from `target` before either runs out:
+                    // nothing under it can start with `target`. Skip the subtree outright.
+                    return;
+                }
+
+                if ext_nibbles.len() <= remaining.len() {
+                    let new_pos = pos + ext_nibbles.len();
+                    collect_prefixed_paths(
+                        nodes,
+                        values,
+                        extension.child_ref,
+                        target,
+                        new_pos,
+                        new_pos >= target.len(),
+                        out,
+                    );
+                } else {
+                    // `target` is fully consumed partway through this extension's own prefix:
+                    // every key under it is longer than `target` and shares all of it.
+                    collect_prefixed_paths(nodes, values, extension.child_ref, target, target.len(), true, out);
+                }
+            }
+        }
+        Some(Node::Leaf(leaf)) => {
+            let (path, _) = values
+                .get(leaf.value_ref.0)
+                .expect("inconsistent internal tree structure");
+
+            // Unlike branches/extensions, a leaf's stored path already covers every nibble from
+            // the tree root onward, so if we haven't matched yet (an ancestor branch dispatched us
+            // here without itself proving the rest of `target`), check the leaf's own path against
+            // the unconsumed suffix of `target` directly.
+            let leaf_matches = matched
+                || remaining.is_empty()
+                || NibbleSlice::new(path.as_ref())
+                    .skip(pos)
+                    .zip(remaining.iter().copied())
+                    .filter(|(a, b)| a == b)
+                    .count()
+                    == remaining.len();
+
+            if leaf_matches {
+                out.push(path.clone());
+            }
+        }
+        None => {}
+    }
+}
+
+/// Collect the [`ProofStep`] that produced each entry of a [`StructuredProof`], in the same
+/// root-to-terminal order [`PatriciaMerkleTree::get_proof`] visits nodes in.
+///
+/// Read-only: unlike the node-level `get_proof`, this never touches the hash caches, so it's safe
+/// to run as a second pass over the same `path` after [`PatriciaMerkleTree::get_proof`] has
+/// already produced the byte chain it's being zipped with.
+fn collect_proof_steps<P, V, H>(
+    nodes: &NodesStorage<P, V, H>,
+    node_ref: NodeRef,
+    target: &[Nibble],
+    pos: usize,
+    out: &mut Vec<ProofStep>,
+) where
+    P: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+    H: Digest,
+{
+    if !node_ref.is_valid() {
+        return;
+    }
+
+    match nodes.get(node_ref.0) {
+        Some(Node::Branch(branch)) => {
+            let choice = target.get(pos).copied();
+            out.push(ProofStep::Branch {
+                choice: choice.map(u8::from),
+            });
+
+            if let Some(choice) = choice {
+                let child_ref = branch.choices[usize::from(choice)];
+                if child_ref.is_valid() {
+                    collect_proof_steps(nodes, child_ref, target, pos + 1, out);
+                }
+            }
+        }
+        Some(Node::Extension(extension)) => {
+            let ext_nibbles: Vec<Nibble> = extension.prefix.iter().collect();
+            let remaining = &target[pos..];
+
+            out.push(ProofStep::Extension {
+                prefix_len: ext_nibbles.len(),
+            });
+
+            if remaining.len() >= ext_nibbles.len() && remaining[..ext_nibbles.len()] == ext_nibbles[..] {
+                collect_proof_steps(nodes, extension.child_ref, target, pos + ext_nibbles.len(), out);
+            }
+        }
+        Some(Node::Leaf(_)) => {
+            out.push(ProofStep::Leaf);
+        }
+        None => {}
+    }
+}
+
+/// Write `bytes` to `w`, preceded by its length, for [`PatriciaMerkleTree::serialize`].
+fn write_framed<W: Write>(w: &mut W, bytes: &[u8]) -> std::io::Result<()> {
+    w.write_all(&(bytes.len() as u64).to_be_bytes())?;
+    w.write_all(bytes)
+}
+
+/// Read back a length-prefixed byte string written by [`write_framed`].
+fn read_framed<R: Read>(r: &mut R) -> std::io::Result<Vec<u8>> {
+    let len = read_u64(r)?;
+    let mut bytes = vec![0u8; len as usize];
+    r.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Read a big-endian `u64`, as used throughout [`PatriciaMerkleTree::serialize`]'s format.
+fn read_u64<R: Read>(r: &mut R) -> std::io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    r.read_exact(&mut bytes)?;
+    Ok(u64::from_be_bytes(bytes))
+}
+
+/// Translate a serialized node index (`u64::MAX` meaning "no node") back into a [`NodeRef`].
+fn node_ref_from_index(index: u64) -> NodeRef {
+    if index == u64::MAX {
+        NodeRef::default()
+    } else {
+        NodeRef(index as usize)
+    }
+}
+
+/// Translate a serialized value index (`u64::MAX` meaning "no value") back into a [`ValueRef`].
+fn value_ref_from_index(index: u64) -> ValueRef {
+    if index == u64::MAX {
+        ValueRef::default()
+    } else {
+        ValueRef(index as usize)
+    }
+}
+
+/// Recursively rebuild one node (and everything beneath it) from a
+/// [`PatriciaMerkleTree::serialize_compact`] stream, inserting into `nodes`/`values` as it goes
+/// and returning a [`NodeRef`] to what it just inserted. `tree`/`values` are cursors into the two
+/// buffers `serialize_compact` produced, advanced as bytes are consumed from them.
+fn decode_compact_node<P, V, H>(
+    tree: &mut &[u8],
+    values: &mut &[u8],
+    nodes: &mut NodesStorage<P, V, H>,
+    values_storage: &mut ValuesStorage<P, V>,
+) -> std::io::Result<NodeRef>
+where
+    P: AsRef<[u8]> + From<Vec<u8>>,
+    V: AsRef<[u8]> + From<Vec<u8>>,
+    H: Digest,
+{
+    let mut tag = [0u8; 1];
+    tree.read_exact(&mut tag)?;
+
+    let node = match tag[0] {
+        0 => {
+            let mut bitmap_bytes = [0u8; 2];
+            tree.read_exact(&mut bitmap_bytes)?;
+            let bitmap = u16::from_be_bytes(bitmap_bytes);
+
+            let mut has_value = [0u8; 1];
+            tree.read_exact(&mut has_value)?;
+
+            let value_ref = if has_value[0] != 0 {
+                let path = P::from(read_framed(values)?);
+                let value = V::from(read_framed(values)?);
+                ValueRef(values_storage.insert((path, value)))
+            } else {
+                ValueRef::default()
+            };
+
+            let mut choices = [NodeRef::default(); 16];
+            for (index, choice) in choices.iter_mut().enumerate() {
+                if bitmap & (1 << index) != 0 {
+                    *choice = decode_compact_node(tree, values, nodes, values_storage)?;
+                }
+            }
+
+            let mut branch = BranchNode::new(choices);
+            branch.update_value_ref(value_ref);
+            Node::Branch(branch)
+        }
+        1 => {
+            let prefix_bytes = read_framed(tree)?;
+            let (prefix, _is_leaf) = NibbleVec::decode_compact(&prefix_bytes);
+            let child_ref = decode_compact_node(tree, values, nodes, values_storage)?;
+            Node::Extension(ExtensionNode::new(prefix, child_ref))
+        }
+        2 => {
+            let path = P::from(read_framed(values)?);
+            let value = V::from(read_framed(values)?);
+            let value_ref = ValueRef(values_storage.insert((path, value)));
+            Node::Leaf(LeafNode::new(value_ref))
+        }
+        _ => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "unknown compact node tag",
+            ))
+        }
+    };
+
+    Ok(NodeRef(nodes.insert(node)))
+}
+
 #[cfg(test)]
 mod test {
     use crate::*;
@@ -361,4 +1456,306 @@ mod test {
         insert_vecs(&mut tree, &vecs);
         check_vecs(&mut tree, &vecs);
     }
+
+    #[test]
+    fn get_proof_verifies_against_root() {
+        let mut tree = PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
+
+        tree.insert(vec![0x12, 0x34], vec![0x01]);
+        tree.insert(vec![0x12, 0x56], vec![0x02]);
+        tree.insert(vec![0xAB], vec![0x03]);
+
+        let root = tree.compute_hash().unwrap();
+        let proof = tree.get_proof(&vec![0x12, 0x34]).unwrap();
+
+        assert!(verify_proof::<Keccak256>(
+            &root,
+            &[0x12, 0x34],
+            &[0x01],
+            &proof
+        ));
+        assert!(!verify_proof::<Keccak256>(
+            &root,
+            &[0x12, 0x34],
+            &[0x02],
+            &proof
+        ));
+    }
+
+    #[test]
+    fn get_structured_proof_reports_branch_choice_and_verifies() {
+        let mut tree = PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
+
+        tree.insert(vec![0x12, 0x34], vec![0x01]);
+        tree.insert(vec![0x12, 0x56], vec![0x02]);
+        tree.insert(vec![0xAB], vec![0x03]);
+
+        let root = tree.compute_hash().unwrap();
+        let proof = tree.get_structured_proof(&vec![0x12, 0x34]).unwrap();
+
+        // 0xAB diverges from the other two keys at the first nibble (1 vs A, the root branch);
+        // 0x12,0x34 and 0x12,0x56 then share nibble [2] (an extension), diverge at the third
+        // nibble (3 vs 5, a branch), and the fourth nibble is resolved directly by the leaf.
+        assert_eq!(
+            proof.0.iter().map(|(step, _)| *step).collect::<Vec<_>>(),
+            vec![
+                ProofStep::Branch { choice: Some(1) },
+                ProofStep::Extension { prefix_len: 1 },
+                ProofStep::Branch { choice: Some(3) },
+                ProofStep::Leaf,
+            ]
+        );
+
+        assert!(verify_structured::<Keccak256>(
+            &root,
+            &[0x12, 0x34],
+            &[0x01],
+            &proof
+        ));
+        assert!(!verify_structured::<Keccak256>(
+            &root,
+            &[0x12, 0x34],
+            &[0x02],
+            &proof
+        ));
+
+        // Mismatched between the actual bytes and the `path` it's checked against: the embedded
+        // chain is still genuine, but the claimed branch choice no longer lines up with `path`.
+        let mut tampered = proof.clone();
+        if let Some((ProofStep::Branch { choice }, _)) = tampered.0.get_mut(2) {
+            *choice = Some(4);
+        }
+        assert!(!verify_structured::<Keccak256>(
+            &root,
+            &[0x12, 0x34],
+            &[0x01],
+            &tampered
+        ));
+
+        assert_eq!(proof.to_proof(), tree.get_proof(&vec![0x12, 0x34]).unwrap());
+    }
+
+    #[test]
+    fn get_exclusion_proof_verifies_absence() {
+        let mut tree = PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
+
+        tree.insert(vec![0x12, 0x34], vec![0x01]);
+        tree.insert(vec![0x12, 0x56], vec![0x02]);
+
+        let root = tree.compute_hash().unwrap();
+        let proof = tree.get_exclusion_proof(&vec![0x12, 0x99]).unwrap();
+
+        assert!(verify_exclusion_proof::<Keccak256>(&root, &proof));
+    }
+
+    #[test]
+    fn verify_proof_rejects_a_genuine_chain_built_for_a_different_key() {
+        // A hash chain that is entirely genuine -- every link really does embed the next, all the
+        // way up to `root` -- but was generated by `get_proof` for a *different* key than the one
+        // `path` claims. Before verify_chain decoded branch choices against `path`, this passed:
+        // the terminal value matched and nothing checked which branch slot the chain actually
+        // descended through.
+        let mut tree = PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
+
+        // Diverge on the very first nibble, so the root is a plain branch (no extension above it
+        // to break path tracking before the branch choice is even reached).
+        tree.insert(vec![0x12], vec![0xAA]);
+        tree.insert(vec![0xAB], vec![0xAA]);
+
+        let root = tree.compute_hash().unwrap();
+        let proof = tree.get_proof(&vec![0xAB]).unwrap();
+
+        assert!(verify_proof::<Keccak256>(&root, &[0xAB], &[0xAA], &proof));
+        assert!(!verify_proof::<Keccak256>(&root, &[0x12], &[0xAA], &proof));
+    }
+
+    #[test]
+    fn get_proof_verifies_over_plain_branch_root() {
+        // Keys diverging on the very first nibble produce a branch root whose children are both
+        // leaves, unlike `get_proof_verifies_against_root`, whose path passes through an extension
+        // below its root branch.
+        let mut tree = PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
+
+        tree.insert(vec![0x12], vec![0x01]);
+        tree.insert(vec![0xAB], vec![0x02]);
+
+        let root = tree.compute_hash().unwrap();
+        let proof = tree.get_proof(&vec![0xAB]).unwrap();
+
+        assert!(verify_proof::<Keccak256>(&root, &[0xAB], &[0x02], &proof));
+    }
+
+    #[test]
+    fn get_batch_proof_verifies_each_path_and_dedupes_shared_nodes() {
+        let mut tree = PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
+
+        tree.insert(vec![0x12, 0x34], vec![0x01]);
+        tree.insert(vec![0x12, 0x56], vec![0x02]);
+        tree.insert(vec![0xAB], vec![0x03]);
+
+        let root = tree.compute_hash().unwrap();
+        let paths = vec![vec![0x12, 0x34], vec![0x12, 0x56]];
+        let proof = tree.get_batch_proof(&paths);
+
+        // The two paths share every node above their divergent leaves, so the batch should carry
+        // fewer distinct encodings than the sum of two independent proofs would.
+        let solo_len = tree.get_proof(&paths[0]).unwrap().0.len() + tree.get_proof(&paths[1]).unwrap().0.len();
+        assert!(proof.nodes.len() < solo_len);
+
+        assert!(verify_batch_proof::<Keccak256>(
+            &root,
+            &[&[0x01], &[0x02]],
+            &proof
+        ));
+        assert!(!verify_batch_proof::<Keccak256>(
+            &root,
+            &[&[0x99], &[0x02]],
+            &proof
+        ));
+    }
+
+    #[test]
+    fn serialize_round_trip() {
+        let mut tree = PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
+
+        tree.insert(vec![0x12, 0x34], vec![0x01]);
+        tree.insert(vec![0x12, 0x56], vec![0x02]);
+        tree.insert(vec![0xAB], vec![0x03]);
+
+        let root = tree.compute_hash().unwrap();
+
+        let mut bytes = Vec::new();
+        tree.serialize(&mut bytes).unwrap();
+        let mut restored =
+            PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::deserialize(&bytes[..]).unwrap();
+
+        assert_eq!(restored.compute_hash().unwrap(), root);
+        assert_eq!(restored.get(&vec![0x12, 0x34]), Some(&vec![0x01]));
+        assert_eq!(restored.get(&vec![0x12, 0x56]), Some(&vec![0x02]));
+        assert_eq!(restored.get(&vec![0xAB]), Some(&vec![0x03]));
+    }
+
+    #[test]
+    fn serialize_round_trip_empty() {
+        let tree = PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
+
+        let mut bytes = Vec::new();
+        tree.serialize(&mut bytes).unwrap();
+        let restored =
+            PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::deserialize(&bytes[..]).unwrap();
+
+        assert!(restored.is_empty());
+    }
+
+    #[test]
+    fn serialize_compact_round_trip() {
+        let mut tree = PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
+
+        tree.insert(vec![0x12, 0x34], vec![0x01]);
+        tree.insert(vec![0x12, 0x56], vec![0x02]);
+        tree.insert(vec![0xAB], vec![0x03]);
+
+        let root = tree.compute_hash().unwrap();
+
+        let (tree_bytes, values_bytes) = tree.serialize_compact();
+        let mut restored = PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::deserialize_compact(
+            &tree_bytes,
+            &values_bytes,
+        )
+        .unwrap();
+
+        assert_eq!(restored.compute_hash().unwrap(), root);
+        assert_eq!(restored.get(&vec![0x12, 0x34]), Some(&vec![0x01]));
+        assert_eq!(restored.get(&vec![0x12, 0x56]), Some(&vec![0x02]));
+        assert_eq!(restored.get(&vec![0xAB]), Some(&vec![0x03]));
+    }
+
+    #[test]
+    fn serialize_compact_round_trip_empty() {
+        let tree = PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
+
+        let (tree_bytes, values_bytes) = tree.serialize_compact();
+        let restored = PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::deserialize_compact(
+            &tree_bytes,
+            &values_bytes,
+        )
+        .unwrap();
+
+        assert!(restored.is_empty());
+    }
+
+    #[test]
+    fn serialize_subtree_covers_only_the_matching_branch() {
+        let mut tree = PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
+
+        tree.insert(vec![0x12, 0x34], vec![0x01]);
+        tree.insert(vec![0x12, 0x56], vec![0x02]);
+        tree.insert(vec![0xAB], vec![0x03]);
+
+        let (tree_bytes, values_bytes) = tree.serialize_subtree(&vec![0x12]).unwrap();
+        assert!(!tree_bytes.is_empty());
+
+        // The values side buffer is a flat sequence of framed (path, value) pairs regardless of
+        // tree shape, so it can be read back directly to check exactly which entries the subtree
+        // walk emitted, without relying on the restored tree's own (prefix-relative) traversal.
+        let mut cursor = &values_bytes[..];
+        let mut entries = Vec::new();
+        while !cursor.is_empty() {
+            let path = read_framed(&mut cursor).unwrap();
+            let value = read_framed(&mut cursor).unwrap();
+            entries.push((path, value));
+        }
+        entries.sort();
+
+        assert_eq!(
+            entries,
+            vec![
+                (vec![0x12, 0x34], vec![0x01]),
+                (vec![0x12, 0x56], vec![0x02]),
+            ]
+        );
+
+        assert!(tree.serialize_subtree(&vec![0xFF]).is_none());
+    }
+
+    #[test]
+    fn drain_prefix_removes_only_matching_entries() {
+        let mut tree = PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
+
+        tree.insert(vec![0x12, 0x34], vec![0x01]);
+        tree.insert(vec![0x12, 0x56], vec![0x02]);
+        tree.insert(vec![0x12], vec![0x03]);
+        tree.insert(vec![0xAB], vec![0x04]);
+
+        let mut drained = tree.drain_prefix(&[0x12]);
+        drained.sort();
+
+        assert_eq!(
+            drained,
+            vec![
+                (vec![0x12], vec![0x03]),
+                (vec![0x12, 0x34], vec![0x01]),
+                (vec![0x12, 0x56], vec![0x02]),
+            ]
+        );
+
+        assert_eq!(tree.get(&vec![0x12, 0x34]), None);
+        assert_eq!(tree.get(&vec![0x12, 0x56]), None);
+        assert_eq!(tree.get(&vec![0x12]), None);
+        assert_eq!(tree.get(&vec![0xAB]), Some(&vec![0x04]));
+    }
+
+    #[test]
+    fn drain_prefix_with_no_matches_leaves_tree_untouched() {
+        let mut tree = PatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
+
+        tree.insert(vec![0x12, 0x34], vec![0x01]);
+        tree.insert(vec![0xAB], vec![0x02]);
+
+        let drained = tree.drain_prefix(&[0xFF]);
+
+        assert!(drained.is_empty());
+        assert_eq!(tree.get(&vec![0x12, 0x34]), Some(&vec![0x01]));
+        assert_eq!(tree.get(&vec![0xAB]), Some(&vec![0x02]));
+    }
 }
@@ -184,6 +184,44 @@ impl<'a> NibbleSlice<'a> {
             }
         })
     }
+
+    /// Hex-prefix encode the nibbles remaining after [`Self::offset`], the same way
+    /// [`NibbleVec::encode_compact`] encodes a whole stored prefix. Useful for encoding a node's
+    /// unconsumed key suffix without first collecting it into a [`NibbleVec`].
+    pub fn encode_compact(&self, is_leaf: bool) -> Vec<u8> {
+        NibbleVec::from_nibbles(self.clone()).encode_compact(is_leaf)
+    }
+
+    /// Compose `self` with a `second` segment that logically follows it — e.g. an extension's
+    /// prefix followed by the branch nibble chosen below it — so both can be iterated (and, via
+    /// [`NibbleVec::from_nibbles`] or [`NibbleVec::encode_compact`], hex-prefix-encoded) as one
+    /// stream without first collecting either into a `Vec`.
+    pub fn new_composed(first: Self, second: Self) -> ComposedNibbles<'a> {
+        ComposedNibbles {
+            first,
+            second: Some(second),
+        }
+    }
+}
+
+/// Two [`NibbleSlice`] segments iterated as a single stream, as built by
+/// [`NibbleSlice::new_composed`].
+#[derive(Clone, Debug)]
+pub struct ComposedNibbles<'a> {
+    first: NibbleSlice<'a>,
+    second: Option<NibbleSlice<'a>>,
+}
+
+impl<'a> Iterator for ComposedNibbles<'a> {
+    type Item = Nibble;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(nibble) = self.first.next() {
+            return Some(nibble);
+        }
+
+        self.second.as_mut()?.next()
+    }
 }
 
 impl<'a> AsRef<[u8]> for NibbleSlice<'a> {
@@ -257,9 +295,65 @@ impl NibbleVec {
         NibbleVecIter {
             inner: self,
             pos: self.first_is_half as usize,
+            end: (self.data.len() << 1) - self.last_is_half as usize,
         }
     }
 
+    /// Number of leading nibbles `self` and `path` (read from its current offset) agree on.
+    ///
+    /// When both sides start at the same nibble alignment, this compares whole bytes at a time
+    /// instead of decoding one nibble per step — the same alignment check [`Self::split_extract_at`]'s
+    /// callers already rely on, generalized from a yes/no prefix check into a length. Nibble-level
+    /// comparison only happens at the unaligned edges: a possible shared leading half-nibble, and
+    /// `self`'s trailing nibble when its length is odd. Misaligned inputs fall back to comparing
+    /// nibble-by-nibble throughout, same as before.
+    pub fn common_prefix(&self, path: &NibbleSlice) -> usize {
+        let self_len = self.iter().count();
+
+        if self.first_is_half != (path.offset() % 2 != 0) {
+            return self
+                .iter()
+                .zip(path.clone())
+                .take_while(|(a, b)| a == b)
+                .count();
+        }
+
+        let self_bytes = self.data.as_slice();
+        let path_bytes = &path.as_ref()[path.offset() >> 1..];
+
+        let (mut count, mut i) = (0, 0);
+        if self.first_is_half {
+            if self_bytes.is_empty()
+                || path_bytes.is_empty()
+                || (self_bytes[0] & 0x0F) != (path_bytes[0] & 0x0F)
+            {
+                return 0;
+            }
+            (count, i) = (1, 1);
+        }
+
+        while self_len - count >= 2 {
+            match (self_bytes.get(i), path_bytes.get(i)) {
+                (Some(&a), Some(&b)) if a == b => {
+                    count += 2;
+                    i += 1;
+                }
+                (Some(&a), Some(&b)) if (a >> 4) == (b >> 4) => return count + 1,
+                _ => return count,
+            }
+        }
+
+        if self_len - count == 1 {
+            if let (Some(&a), Some(&b)) = (self_bytes.get(i), path_bytes.get(i)) {
+                if (a >> 4) == (b >> 4) {
+                    count += 1;
+                }
+            }
+        }
+
+        count
+    }
+
     pub fn split_extract_at(self, index: usize) -> (NibbleVec, Nibble, NibbleVec) {
         // println!("  data = {:x?}", self.data.as_slice());
         // println!("  first_is_half = {}", self.first_is_half);
@@ -318,37 +412,138 @@ impl NibbleVec {
             self.last_is_half = false;
         }
     }
+
+    /// Hex-prefix encode this nibble sequence (Ethereum's compact path encoding), so it packs two
+    /// nibbles per byte instead of one, and round-trips through [`Self::decode_compact`].
+    ///
+    /// The leading nibble of the first byte is a flag: bit 1 marks a leaf path (vs. an extension
+    /// path), bit 0 marks an odd nibble count. For an odd count the first real nibble is folded
+    /// into the low half of the flag byte; for an even count the flag byte is padded with zeroes.
+    pub fn encode_compact(&self, is_leaf: bool) -> Vec<u8> {
+        let nibbles: Vec<Nibble> = self.iter().collect();
+        let odd = nibbles.len() % 2 != 0;
+        let flag = ((is_leaf as u8) << 1) | (odd as u8);
+
+        let mut rest = nibbles.as_slice();
+        let mut out = Vec::with_capacity(nibbles.len() / 2 + 1);
+        if odd {
+            out.push((flag << 4) | u8::from(rest[0]));
+            rest = &rest[1..];
+        } else {
+            out.push(flag << 4);
+        }
+
+        for pair in rest.chunks(2) {
+            out.push((u8::from(pair[0]) << 4) | u8::from(pair[1]));
+        }
+
+        out
+    }
+
+    /// Decode a hex-prefix encoded path produced by [`Self::encode_compact`], returning the
+    /// nibble sequence and whether it was flagged as a leaf path.
+    pub fn decode_compact(bytes: &[u8]) -> (Self, bool) {
+        let Some((&first, rest)) = bytes.split_first() else {
+            return (Self::new(), false);
+        };
+
+        let flag = first >> 4;
+        let is_leaf = flag & 0b10 != 0;
+        let odd = flag & 0b01 != 0;
+
+        let mut nibbles = Vec::with_capacity(rest.len() * 2 + 1);
+        if odd {
+            nibbles.push(nibble_from(first & 0x0F));
+        }
+        for &byte in rest {
+            nibbles.push(nibble_from(byte >> 4));
+            nibbles.push(nibble_from(byte & 0x0F));
+        }
+
+        (Self::from_nibbles(nibbles.into_iter()), is_leaf)
+    }
+
+    /// Alias for [`Self::encode_compact`] matching the Ethereum yellow paper's "hex-prefix"
+    /// naming, for callers coming from that spec rather than this crate's own vocabulary.
+    pub fn encode_hex_prefix(&self, is_leaf: bool) -> Vec<u8> {
+        self.encode_compact(is_leaf)
+    }
+
+    /// Alias for [`Self::decode_compact`]; see [`Self::encode_hex_prefix`].
+    pub fn decode_hex_prefix(bytes: &[u8]) -> (Self, bool) {
+        Self::decode_compact(bytes)
+    }
+}
+
+fn nibble_from(value: u8) -> Nibble {
+    Nibble::try_from(value).expect("value is masked to 4 bits")
 }
 
 pub struct NibbleVecIter<'a> {
     inner: &'a NibbleVec,
+    /// Nibble index of the next nibble `next()` will yield.
     pos: usize,
+    /// Nibble index one past the last nibble `next_back()` will yield.
+    end: usize,
+}
+
+impl<'a> NibbleVecIter<'a> {
+    /// The nibble at nibble index `index`, which must lie within `[pos, end)`.
+    fn nibble_at(&self, index: usize) -> Nibble {
+        let byte = if index % 2 != 0 {
+            self.inner.data[index >> 1] & 0x0F
+        } else {
+            self.inner.data[index >> 1] >> 4
+        };
+
+        match Nibble::try_from(byte) {
+            Ok(x) => x,
+            Err(_) => unreachable!(),
+        }
+    }
 }
 
 impl<'a> Iterator for NibbleVecIter<'a> {
     type Item = Nibble;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.data.get(self.pos >> 1).and_then(|byte| {
-            if (self.pos >> 1) + 1 == self.inner.data.len()
-                && self.pos % 2 == 1
-                && self.inner.last_is_half
-            {
-                return None;
-            }
+        if self.pos >= self.end {
+            return None;
+        }
 
-            let byte = if self.pos % 2 != 0 {
-                byte & 0x0F
-            } else {
-                byte >> 4
-            };
+        let nibble = self.nibble_at(self.pos);
+        self.pos += 1;
+        Some(nibble)
+    }
 
-            self.pos += 1;
-            match Nibble::try_from(byte) {
-                Ok(x) => Some(x),
-                Err(_) => unreachable!(),
-            }
-        })
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+
+    /// Skip `n` nibbles in O(1), rather than `next()`ing through them one at a time.
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.pos = self.pos.saturating_add(n);
+        self.next()
+    }
+}
+
+/// Yields nibbles from the end, honoring `last_is_half` symmetrically to how `next()` honors
+/// `first_is_half`.
+impl<'a> DoubleEndedIterator for NibbleVecIter<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.end {
+            return None;
+        }
+
+        self.end -= 1;
+        Some(self.nibble_at(self.end))
+    }
+}
+
+impl<'a> ExactSizeIterator for NibbleVecIter<'a> {
+    fn len(&self) -> usize {
+        self.end - self.pos
     }
 }
 
@@ -525,6 +720,80 @@ mod test {
         });
     }
 
+    #[test]
+    fn nibble_vec_common_prefix_full_match_aligned() {
+        let prefix = NibbleVec {
+            data: SmallVec::from_slice(&[0x12, 0x34]),
+            first_is_half: false,
+            last_is_half: false,
+        };
+        let path = NibbleSlice {
+            data: &[0x12, 0x34, 0x56],
+            offset: 0,
+        };
+        assert_eq!(prefix.common_prefix(&path), 4);
+    }
+
+    #[test]
+    fn nibble_vec_common_prefix_partial_match_aligned() {
+        let prefix = NibbleVec {
+            data: SmallVec::from_slice(&[0x12, 0x34]),
+            first_is_half: false,
+            last_is_half: false,
+        };
+        let path = NibbleSlice {
+            data: &[0x12, 0x54, 0x56],
+            offset: 0,
+        };
+        // Shares [1, 2] but diverges on the third nibble (3 vs 5).
+        assert_eq!(prefix.common_prefix(&path), 2);
+    }
+
+    #[test]
+    fn nibble_vec_common_prefix_odd_length() {
+        let prefix = NibbleVec {
+            data: SmallVec::from_slice(&[0x12, 0x30]),
+            first_is_half: false,
+            last_is_half: true,
+        };
+        let path = NibbleSlice {
+            data: &[0x12, 0x3F, 0x56],
+            offset: 0,
+        };
+        // Prefix only has 3 live nibbles ([1, 2, 3]); the padding low nibble must not be compared.
+        assert_eq!(prefix.common_prefix(&path), 3);
+    }
+
+    #[test]
+    fn nibble_vec_common_prefix_unaligned_falls_back_to_nibbles() {
+        let prefix = NibbleVec {
+            data: SmallVec::from_slice(&[0x12]),
+            first_is_half: false,
+            last_is_half: false,
+        };
+        let path = NibbleSlice {
+            data: &[0xF1, 0x23],
+            offset: 1,
+        };
+        // `prefix` starts on a byte boundary but `path` starts mid-byte, so the aligned byte-wise
+        // fast path doesn't apply and this exercises the nibble-at-a-time fallback.
+        assert_eq!(prefix.common_prefix(&path), 2);
+    }
+
+    #[test]
+    fn nibble_vec_common_prefix_no_match() {
+        let prefix = NibbleVec {
+            data: SmallVec::from_slice(&[0x12, 0x34]),
+            first_is_half: false,
+            last_is_half: false,
+        };
+        let path = NibbleSlice {
+            data: &[0xFF, 0x34, 0x56],
+            offset: 0,
+        };
+        assert_eq!(prefix.common_prefix(&path), 0);
+    }
+
     #[test]
     fn nibble_slice_cmp_rest_success() {
         let slice = NibbleSlice {
@@ -907,4 +1176,83 @@ mod test {
         assert_eq!(vec_iter.next(), None);
         assert_eq!(vec_iter.pos, 5);
     }
+
+    #[test]
+    fn nibble_vec_iter_next_back() {
+        let vec = NibbleVec {
+            data: SmallVec::from_slice(&[0x12, 0x34, 0x56]),
+            first_is_half: false,
+            last_is_half: false,
+        };
+        let mut vec_iter = vec.iter();
+
+        assert_eq!(vec_iter.len(), 6);
+        assert_eq!(vec_iter.next_back(), Some(Nibble::V6));
+        assert_eq!(vec_iter.next(), Some(Nibble::V1));
+        assert_eq!(vec_iter.next_back(), Some(Nibble::V5));
+        assert_eq!(vec_iter.next_back(), Some(Nibble::V4));
+        assert_eq!(vec_iter.next(), Some(Nibble::V2));
+        assert_eq!(vec_iter.next(), Some(Nibble::V3));
+        assert_eq!(vec_iter.len(), 0);
+        assert_eq!(vec_iter.next(), None);
+        assert_eq!(vec_iter.next_back(), None);
+    }
+
+    #[test]
+    fn nibble_vec_iter_next_back_first_last_half() {
+        let vec = NibbleVec {
+            data: SmallVec::from_slice(&[0x12, 0x34, 0x56]),
+            first_is_half: true,
+            last_is_half: true,
+        };
+        let mut vec_iter = vec.iter();
+
+        assert_eq!(vec_iter.len(), 4);
+        assert_eq!(vec_iter.next_back(), Some(Nibble::V5));
+        assert_eq!(vec_iter.next_back(), Some(Nibble::V4));
+        assert_eq!(vec_iter.next_back(), Some(Nibble::V3));
+        assert_eq!(vec_iter.next_back(), Some(Nibble::V2));
+        assert_eq!(vec_iter.next_back(), None);
+        assert_eq!(vec_iter.len(), 0);
+    }
+
+    #[test]
+    fn nibble_vec_iter_nth() {
+        let vec = NibbleVec {
+            data: SmallVec::from_slice(&[0x12, 0x34, 0x56]),
+            first_is_half: false,
+            last_is_half: false,
+        };
+
+        assert_eq!(vec.iter().nth(0), Some(Nibble::V1));
+        assert_eq!(vec.iter().nth(3), Some(Nibble::V4));
+        assert_eq!(vec.iter().nth(5), Some(Nibble::V6));
+        assert_eq!(vec.iter().nth(6), None);
+
+        let mut vec_iter = vec.iter();
+        assert_eq!(vec_iter.nth(1), Some(Nibble::V2));
+        assert_eq!(vec_iter.next(), Some(Nibble::V3));
+    }
+
+    #[test]
+    fn nibble_slice_new_composed() {
+        let first = NibbleSlice::new(&[0x12]);
+        let second = NibbleSlice::new(&[0x34]);
+
+        let composed: Vec<_> = NibbleSlice::new_composed(first, second).collect();
+        assert_eq!(
+            composed,
+            vec![Nibble::V1, Nibble::V2, Nibble::V3, Nibble::V4],
+        );
+    }
+
+    #[test]
+    fn nibble_slice_new_composed_with_offset() {
+        let mut first = NibbleSlice::new(&[0x12]);
+        first.offset_add(1);
+        let second = NibbleSlice::new(&[0x34]);
+
+        let composed: Vec<_> = NibbleSlice::new_composed(first, second).collect();
+        assert_eq!(composed, vec![Nibble::V2, Nibble::V3, Nibble::V4]);
+    }
 }
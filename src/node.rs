@@ -62,6 +62,24 @@ where
         }
     }
 
+    /// Collect the encoded form of every node visited while resolving `path_iter`, from this node
+    /// down to the terminal node (inclusive). Used to build Merkle proofs.
+    pub fn get_proof(
+        &mut self,
+        nodes: &mut NodesStorage<P, V, H>,
+        values: &ValuesStorage<P, V>,
+        path: Offseted<impl Iterator<Item = Nibble>>,
+        proof: &mut Vec<Vec<u8>>,
+    ) {
+        match self {
+            Node::Branch(branch_node) => branch_node.get_proof(nodes, values, path, proof),
+            Node::Extension(extension_node) => {
+                extension_node.get_proof(nodes, values, path, proof)
+            }
+            Node::Leaf(leaf_node) => leaf_node.get_proof(nodes, values, path, proof),
+        }
+    }
+
     pub fn remove<I>(
         self,
         nodes: &mut NodesStorage<P, V, H>,
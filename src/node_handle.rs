@@ -0,0 +1,164 @@
+//! A lazily-resolvable node reference, in the spirit of OpenEthereum's `NodeHandle` /
+//! `Node::Hash`: a child link that is either already materialized in the arena or merely a hash
+//! waiting to be fetched (via [`crate::backend::NodeBackend`] or [`crate::store::NodeStore`]) the
+//! first time traversal actually reaches it.
+//!
+//! NOTE: same caveat as [`crate::backend`]/[`crate::store`] — replacing `NodeRef`'s plain
+//! arena index with this handle inside `BranchNode`/`ExtensionNode`'s `choices`, and threading a
+//! backend lookup through every `get`/`insert`/`remove`/`drain_filter` call site so a `Hash`
+//! handle resolves (and optionally caches back as `InMemory`) on demand, is a larger change than
+//! fits in one pass. This module defines the handle those call sites would match on.
+
+use crate::{node::Node, NodesStorage};
+use digest::{Digest, Output};
+
+/// Either a child already living in the node arena, or a hash to be resolved on demand.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NodeHandle<H>
+where
+    H: Digest,
+{
+    /// The child is materialized at this arena index.
+    InMemory(usize),
+    /// The child is not loaded; only its hash is known. Resolving it requires a backend lookup.
+    Hash(Output<H>),
+}
+
+impl<H> NodeHandle<H>
+where
+    H: Digest,
+{
+    /// Whether this handle already points at an arena-resident node.
+    pub fn is_in_memory(&self) -> bool {
+        matches!(self, Self::InMemory(_))
+    }
+
+    /// The arena index, if this handle is already resolved.
+    pub fn as_in_memory(&self) -> Option<usize> {
+        match self {
+            Self::InMemory(index) => Some(*index),
+            Self::Hash(_) => None,
+        }
+    }
+
+    /// The unresolved hash, if this handle hasn't been loaded yet.
+    pub fn as_hash(&self) -> Option<&Output<H>> {
+        match self {
+            Self::InMemory(_) => None,
+            Self::Hash(hash) => Some(hash),
+        }
+    }
+}
+
+/// Decodes a node's on-disk bytes (keyed by its hash) back into an owned [`Node`], the way a
+/// `HashDB`-backed trie resolves a [`NodeHandle::Hash`] the first time traversal actually reaches
+/// it.
+pub trait NodeLoader<P, V, H>
+where
+    P: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+    H: Digest,
+{
+    fn load(&self, hash: &Output<H>) -> Option<Node<P, V, H>>;
+}
+
+/// Resolve `handle` to an arena index, loading it through `loader` on first access if it's still
+/// just a hash. The decoded node is inserted into `nodes` and `handle` is updated in place to
+/// [`NodeHandle::InMemory`], so resolving the same handle again is a plain lookup rather than
+/// another `loader` call — mirroring how a real `HashDB`-backed trie caches a page once it's
+/// paged in.
+///
+/// Returns `None` if `loader` has no record for `handle`'s hash (a corrupted or incomplete
+/// backing store), leaving `handle` untouched.
+///
+/// NOTE: nothing in `BranchNode`/`ExtensionNode` actually stores a `NodeHandle` yet — their
+/// `choices`/`child_ref` fields are still a plain [`crate::NodeRef`] into an always-resident
+/// arena (see this module's top-level NOTE). This is the resolution step those call sites would
+/// use once they do.
+pub fn resolve<P, V, H>(
+    handle: &mut NodeHandle<H>,
+    nodes: &mut NodesStorage<P, V, H>,
+    loader: &dyn NodeLoader<P, V, H>,
+) -> Option<usize>
+where
+    P: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+    H: Digest,
+{
+    match handle {
+        NodeHandle::InMemory(index) => Some(*index),
+        NodeHandle::Hash(hash) => {
+            let node = loader.load(hash)?;
+            let index = nodes.insert(node);
+            *handle = NodeHandle::InMemory(index);
+            Some(index)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{pmt_node, pmt_state};
+    use sha3::Keccak256;
+    use std::collections::HashMap;
+
+    struct StubLoader(HashMap<Output<Keccak256>, Node<Vec<u8>, Vec<u8>, Keccak256>>);
+
+    impl NodeLoader<Vec<u8>, Vec<u8>, Keccak256> for StubLoader {
+        fn load(&self, hash: &Output<Keccak256>) -> Option<Node<Vec<u8>, Vec<u8>, Keccak256>> {
+            self.0.get(hash).cloned()
+        }
+    }
+
+    #[test]
+    fn resolve_is_a_no_op_once_already_in_memory() {
+        let (mut nodes, mut values) = pmt_state!(Vec<u8>);
+        let leaf = pmt_node! { @(nodes, values) leaf { vec![0x12] => vec![0x34] } };
+        let index = nodes.insert(leaf);
+
+        let mut handle = NodeHandle::<Keccak256>::InMemory(index);
+        let loader = StubLoader(HashMap::new());
+
+        assert_eq!(resolve(&mut handle, &mut nodes, &loader), Some(index));
+        assert_eq!(handle, NodeHandle::InMemory(index));
+    }
+
+    #[test]
+    fn resolve_loads_a_hash_handle_and_caches_it_in_memory() {
+        let (mut nodes, mut values) = pmt_state!(Vec<u8>);
+        let leaf = pmt_node! { @(nodes, values) leaf { vec![0x12] => vec![0x34] } };
+
+        let hash = Output::<Keccak256>::default();
+        let mut loaded = HashMap::new();
+        loaded.insert(hash.clone(), leaf);
+        let loader = StubLoader(loaded);
+
+        let mut handle = NodeHandle::Hash(hash);
+        assert!(handle.as_hash().is_some());
+
+        let index = resolve(&mut handle, &mut nodes, &loader).expect("loader has this hash");
+
+        assert!(handle.is_in_memory());
+        assert_eq!(handle.as_in_memory(), Some(index));
+        assert!(nodes.get(index).is_some());
+
+        // Resolving again is now a plain lookup, not another `loader` call.
+        let loader_with_nothing_loaded = StubLoader(HashMap::new());
+        assert_eq!(
+            resolve(&mut handle, &mut nodes, &loader_with_nothing_loaded),
+            Some(index)
+        );
+    }
+
+    #[test]
+    fn resolve_returns_none_for_an_unknown_hash() {
+        let (mut nodes, _) = pmt_state!(Vec<u8>);
+        let loader = StubLoader(HashMap::new());
+
+        let mut handle = NodeHandle::<Keccak256>::Hash(Output::<Keccak256>::default());
+        assert_eq!(resolve(&mut handle, &mut nodes, &loader), None);
+        // Left untouched on failure.
+        assert!(!handle.is_in_memory());
+    }
+}
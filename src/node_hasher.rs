@@ -0,0 +1,88 @@
+//! An abstraction over node hashing that doesn't assume a byte-oriented digest, so arithmetic
+//! hashes (Poseidon, Pedersen) used by zk-friendly commitments could eventually sit alongside
+//! Keccak256.
+//!
+//! NOTE: `PatriciaMerkleTree`, `compute_hash`, and the node modules still hard-code an `H:
+//! Digest` bound end to end (see `hashing.rs`, `node.rs`, `nodes/*.rs`) rather than going through
+//! [`NodeHasher::hash_children`]. [`DigestHasher::hash_children`] concatenates children and
+//! hashes the result directly, which is not the same byte sequence `compute_hash`'s RLP-style
+//! encoding feeds `H` — wiring it in as the node modules' hashing path would change every hash
+//! the crate produces, breaking the hardcoded root-hash test vectors already committed elsewhere
+//! in this crate. That migration (changing `compute_hash`'s own encoding to match, not just its
+//! bound) is left for a follow-up; what's here is the trait such a refactor would land on, plus a
+//! blanket adapter so every existing `Digest` keeps working unchanged once it does.
+
+use digest::{Digest, Output};
+use std::marker::PhantomData;
+
+/// A hash function over node children, parameterized over its own output type rather than a
+/// fixed byte string — so an implementation backed by a prime field (e.g. Poseidon's `Fr`) never
+/// has to round-trip through bytes just to satisfy this trait.
+pub trait NodeHasher {
+    /// A single node hash, e.g. `Output<H>` for a byte-oriented digest or a field element `Fr`
+    /// for an arithmetic hash.
+    type Output: Clone + Eq;
+
+    /// Combine a node's children hashes into this node's own hash.
+    fn hash_children(children: &[Self::Output]) -> Self::Output;
+
+    /// Encode an output as bytes, e.g. for storage or inclusion in a proof.
+    fn serialize(output: &Self::Output) -> Vec<u8>;
+
+    /// Decode an output previously produced by [`Self::serialize`].
+    fn deserialize(bytes: &[u8]) -> Self::Output;
+}
+
+/// Adapts any `digest::Digest` into a [`NodeHasher`], preserving the byte-oriented behavior this
+/// crate already relies on so existing `H: Digest` users are unaffected by the trait's existence.
+pub struct DigestHasher<H>(PhantomData<H>);
+
+impl<H> NodeHasher for DigestHasher<H>
+where
+    H: Digest,
+{
+    type Output = Output<H>;
+
+    fn hash_children(children: &[Self::Output]) -> Self::Output {
+        let mut hasher = H::new();
+        for child in children {
+            Digest::update(&mut hasher, child);
+        }
+        hasher.finalize()
+    }
+
+    fn serialize(output: &Self::Output) -> Vec<u8> {
+        output.to_vec()
+    }
+
+    fn deserialize(bytes: &[u8]) -> Self::Output {
+        Output::<H>::clone_from_slice(bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha3::Keccak256;
+
+    #[test]
+    fn hash_children_matches_digest_over_the_concatenated_children() {
+        let left = DigestHasher::<Keccak256>::deserialize(&Keccak256::digest(b"left"));
+        let right = DigestHasher::<Keccak256>::deserialize(&Keccak256::digest(b"right"));
+
+        let combined = DigestHasher::<Keccak256>::hash_children(&[left.clone(), right.clone()]);
+
+        let mut expected = Keccak256::new();
+        Digest::update(&mut expected, &left);
+        Digest::update(&mut expected, &right);
+        assert_eq!(combined, expected.finalize());
+    }
+
+    #[test]
+    fn serialize_deserialize_round_trip() {
+        let hash = Keccak256::digest(b"round-trip me");
+        let output = DigestHasher::<Keccak256>::deserialize(&hash);
+
+        assert_eq!(DigestHasher::<Keccak256>::serialize(&output), hash.to_vec());
+    }
+}
@@ -1,11 +1,11 @@
-use super::LeafNode;
+use super::{ExtensionNode, LeafNode};
 use crate::{
-    hashing::{NodeHash, NodeHashRef, NodeHasher},
-    nibble::NibbleSlice,
+    hashing::{NodeDecoder, NodeHash, NodeHashRef, NodeHasher},
+    nibble::{Nibble, NibbleSlice, NibbleVec},
     node::{InsertAction, Node},
     NodeRef, NodesStorage, ValueRef, ValuesStorage,
 };
-use digest::Digest;
+use digest::{Digest, Output};
 use std::marker::PhantomData;
 
 #[derive(Clone, Debug)]
@@ -122,6 +122,94 @@ where
         (self.into(), insert_action)
     }
 
+    /// Remove the value at `path`, collapsing this node's structure as needed to keep the tree
+    /// canonical: a branch left with a single child and no value collapses into an extension (or
+    /// merges with an extension child), and a branch left with only a value becomes a leaf.
+    pub(crate) fn remove(
+        mut self,
+        nodes: &mut NodesStorage<P, V, H>,
+        values: &mut ValuesStorage<P, V>,
+        mut path: NibbleSlice,
+    ) -> (Option<Node<P, V, H>>, Option<V>) {
+        self.hash.mark_as_dirty();
+
+        let old_value = match path.next().map(usize::from) {
+            Some(choice) => {
+                let child_ref = self.choices[choice];
+                if child_ref.is_valid() {
+                    let child_node = nodes
+                        .try_remove(*child_ref)
+                        .expect("inconsistent internal tree structure");
+
+                    let (child_node, old_value) = child_node.remove(nodes, values, path);
+                    self.choices[choice] = match child_node {
+                        Some(child_node) => NodeRef::new(nodes.insert(child_node)),
+                        None => Default::default(),
+                    };
+
+                    old_value
+                } else {
+                    None
+                }
+            }
+            None => {
+                if self.value_ref.is_valid() {
+                    let (_, value) = values
+                        .try_remove(*self.value_ref)
+                        .expect("inconsistent internal tree structure");
+
+                    self.value_ref = Default::default();
+                    Some(value)
+                } else {
+                    None
+                }
+            }
+        };
+
+        let mut children = self
+            .choices
+            .iter()
+            .enumerate()
+            .filter(|(_, choice)| choice.is_valid());
+        let single_child = children.next();
+        let has_more_children = children.next().is_some();
+
+        let new_node = if has_more_children || (single_child.is_some() && self.value_ref.is_valid())
+        {
+            Some(self.into())
+        } else if let Some((index, &child_ref)) = single_child {
+            // Exactly one child and no value: collapse into an extension over that child (merging
+            // with its prefix if it is itself an extension).
+            let nibble = Nibble::try_from(index as u8).expect("index is a valid nibble");
+            let child_node = nodes
+                .try_remove(*child_ref)
+                .expect("inconsistent internal tree structure");
+
+            match child_node {
+                Node::Extension(child_ext) => {
+                    let merged_prefix = NibbleVec::from_nibbles(
+                        std::iter::once(nibble).chain(child_ext.prefix.iter()),
+                    );
+
+                    Some(ExtensionNode::new(merged_prefix, child_ext.child_ref).into())
+                }
+                child_node => {
+                    let child_ref = NodeRef::new(nodes.insert(child_node));
+                    let prefix = NibbleVec::from_nibbles(std::iter::once(nibble));
+
+                    Some(ExtensionNode::new(prefix, child_ref).into())
+                }
+            }
+        } else if self.value_ref.is_valid() {
+            // No children left, but a value remains: this branch becomes a leaf.
+            Some(LeafNode::new(self.value_ref).into())
+        } else {
+            None
+        };
+
+        (new_node, old_value)
+    }
+
     pub fn compute_hash(
         &self,
         nodes: &NodesStorage<P, V, H>,
@@ -196,6 +284,59 @@ where
             hasher.finalize()
         })
     }
+
+    /// Recover each choice's raw child reference (`None` for an absent choice) and the node's own
+    /// value, from `encoded` — the output of [`Self::compute_hash`] — the inverse of that method's
+    /// encoding. A present choice comes back as its 32-byte hash (a child short enough to have
+    /// been inlined can't be recovered, see the caveat below); the value comes back as its raw
+    /// bytes, or `None` if this branch has none of its own.
+    ///
+    /// Returns `None` if `encoded` doesn't parse as a list of 17 length-prefixed items — which is
+    /// always the case if any of the original choices was itself inlined rather than hashed, since
+    /// [`crate::hashing::NodeHasher::write_raw`] writes that child's bytes with no length prefix of
+    /// their own (see [`crate::hashing::NodeDecoder`]'s NOTE). Recovering an inlined child requires
+    /// the caller to already have it in memory rather than roundtripping it through this format.
+    pub fn decode_choices(encoded: &[u8]) -> Option<([Option<Output<H>>; 16], Option<Vec<u8>>)> {
+        let mut decoder = NodeDecoder::new(encoded)?;
+
+        let mut choices: [Option<Output<H>>; 16] = Default::default();
+        for choice in &mut choices {
+            let data = decoder.read_bytes()?;
+            *choice = (!data.is_empty()).then(|| Output::<H>::clone_from_slice(data));
+        }
+
+        let value = decoder.read_bytes()?;
+        let value = (!value.is_empty()).then(|| value.to_vec());
+
+        Some((choices, value))
+    }
+
+    /// Collect the encoded form of every node visited while resolving `path`, from this node down
+    /// to the terminal node (inclusive). Used to build Merkle proofs.
+    pub(crate) fn get_proof<'a>(
+        &'a self,
+        nodes: &'a NodesStorage<P, V, H>,
+        values: &'a ValuesStorage<P, V>,
+        mut path: NibbleSlice,
+        proof: &mut Vec<Vec<u8>>,
+    ) {
+        proof.push(
+            self.compute_hash(nodes, values, path.offset())
+                .as_ref()
+                .to_vec(),
+        );
+
+        if let Some(choice) = path.next().map(usize::from) {
+            let child_ref = self.choices[choice];
+            if child_ref.is_valid() {
+                let child_node = nodes
+                    .get(*child_ref)
+                    .expect("inconsistent internal tree structure");
+
+                child_node.get_proof(nodes, values, path, proof);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -456,4 +597,77 @@ mod test {
             ],
         );
     }
+
+    #[test]
+    fn decode_choices_reads_back_a_list_of_absent_and_hashed_children() {
+        // Assembled by hand in the same length-prefixed-item format `compute_hash` writes via
+        // `NodeHasher`, rather than through an actual `BranchNode` — real leaf/branch children
+        // would need to hash to 32+ bytes to land here as a `Hash` rather than an `Inline`, which
+        // `decode_choices` can't read back anyway (see its doc comment).
+        fn push_item(buf: &mut Vec<u8>, data: &[u8]) {
+            buf.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            buf.extend_from_slice(data);
+        }
+
+        let child_hash = Keccak256::digest(b"some child node");
+
+        let mut payload = Vec::new();
+        push_item(&mut payload, b"");
+        push_item(&mut payload, &child_hash);
+        for _ in 0..14 {
+            push_item(&mut payload, b"");
+        }
+        push_item(&mut payload, b"own value");
+
+        let mut encoded = (payload.len() as u64).to_be_bytes().to_vec();
+        encoded.extend_from_slice(&payload);
+
+        let (choices, value) = BranchNode::<Vec<u8>, Vec<u8>, Keccak256>::decode_choices(&encoded)
+            .expect("a well-formed encoding decodes");
+
+        assert_eq!(choices[0], None);
+        assert_eq!(choices[1], Some(child_hash));
+        assert!(choices[2..].iter().all(Option::is_none));
+        assert_eq!(value, Some(b"own value".to_vec()));
+    }
+
+    #[test]
+    fn remove_collapses_to_extension() {
+        let (mut nodes, mut values) = pmt_state!(Vec<u8>);
+
+        let node = pmt_node! { @(nodes, values)
+            branch {
+                2 => leaf { vec![0x20] => vec![0x20] },
+                4 => leaf { vec![0x40] => vec![0x40] },
+            }
+        };
+
+        let (node, old_value) = node.remove(&mut nodes, &mut values, NibbleSlice::new(&[0x20]));
+        let node = match node {
+            Some(Node::Extension(x)) => x,
+            _ => panic!("expected an extension node"),
+        };
+
+        assert_eq!(node.prefix.iter().collect::<Vec<_>>(), vec![Nibble::V4]);
+        assert_eq!(old_value, Some(vec![0x20]));
+    }
+
+    #[test]
+    fn remove_collapses_to_leaf() {
+        let (mut nodes, mut values) = pmt_state!(Vec<u8>);
+
+        let node = pmt_node! { @(nodes, values)
+            branch {
+                2 => leaf { vec![0x20] => vec![0x20] },
+            } with_leaf { vec![] => vec![0x99] }
+        };
+
+        let (node, old_value) = node.remove(&mut nodes, &mut values, NibbleSlice::new(&[0x20]));
+        let _ = match node {
+            Some(Node::Leaf(x)) => x,
+            _ => panic!("expected a leaf node"),
+        };
+
+        assert_eq!(old_value, Some(vec![0x20]));
+    }
 }
@@ -15,10 +15,12 @@ where
     V: AsRef<[u8]>,
     H: Digest,
 {
-    prefix: NibbleVec,
+    // `NibbleVec` already stores two nibbles per byte (see `nibble.rs`'s `data: SmallVec<[u8; 64]>`), so this
+    // was never a flat one-nibble-per-byte `Vec<Nibble>`.
+    pub(crate) prefix: NibbleVec,
     // The child node may only be a branch, but it's not included directly by value to avoid
     // inflating `Node`'s size too much.
-    child_ref: NodeRef,
+    pub(crate) child_ref: NodeRef,
 
     hash: (usize, Output<H>),
     phantom: PhantomData<(P, V, H)>,
@@ -39,6 +41,16 @@ where
         }
     }
 
+    /// Hex-prefix encode this node's partial path, for interop with tooling that expects the
+    /// Ethereum-compatible compact encoding rather than this crate's own two-nibble-per-byte
+    /// `NibbleVec` layout.
+    ///
+    /// Note: `LeafNode` in this tree does not hold a partial path of its own (it defers entirely
+    /// to the raw key stored alongside its value), so there is no equivalent method there.
+    pub(crate) fn encode_compact_prefix(&self) -> Vec<u8> {
+        self.prefix.encode_compact(false)
+    }
+
     pub fn get<'a>(
         &self,
         nodes: &'a NodesStorage<P, V, H>,
@@ -89,13 +101,7 @@ where
             let insert_action = insert_action.quantize_self(self.child_ref);
             (self.into(), insert_action)
         } else {
-            // TODO: Implement dedicated method (avoid half-byte iterators).
-            let offset = self
-                .prefix
-                .iter()
-                .zip(path.clone())
-                .take_while(|(a, b)| a == b)
-                .count();
+            let offset = self.prefix.common_prefix(&path);
             assert!(
                 offset < self.prefix.iter().count(),
                 "{:#02x?}, {:#02x?}",
@@ -164,6 +170,74 @@ where
 
         &self.hash.1
     }
+
+    /// Remove the value at `path`, collapsing this node's structure as needed to keep the tree
+    /// canonical.
+    ///
+    /// Returns `None` in place of the node when the subtree below it has become empty, and the
+    /// removed value (if any).
+    pub(crate) fn remove(
+        mut self,
+        nodes: &mut NodesStorage<P, V, H>,
+        values: &mut ValuesStorage<P, V>,
+        mut path: NibbleSlice,
+    ) -> (Option<Node<P, V, H>>, Option<V>) {
+        if !path.skip_prefix(&self.prefix) {
+            return (Some(self.into()), None);
+        }
+
+        self.hash.0 = 0;
+
+        let child_node = nodes
+            .try_remove(self.child_ref.0)
+            .expect("inconsistent internal tree structure");
+
+        let (child_node, old_value) = child_node.remove(nodes, values, path);
+
+        let new_node = match child_node {
+            None => None,
+            // Two adjacent extensions must be merged into a single one.
+            Some(Node::Extension(child_ext)) => {
+                let merged_prefix =
+                    NibbleVec::from_nibbles(self.prefix.iter().chain(child_ext.prefix.iter()));
+
+                Some(ExtensionNode::new(merged_prefix, child_ext.child_ref).into())
+            }
+            // A branch collapsing down to its one remaining value becomes a leaf with no path of
+            // its own (it derives its key from the stored value directly, regardless of how many
+            // nibbles were already consumed to reach it) — wrapping it in this node's prefix
+            // would leave a redundant extension-over-leaf that a canonical trie never produces,
+            // so the leaf replaces this node outright instead.
+            Some(Node::Leaf(leaf)) => Some(leaf.into()),
+            Some(child_node) => {
+                self.child_ref = NodeRef(nodes.insert(child_node));
+                Some(self.into())
+            }
+        };
+
+        (new_node, old_value)
+    }
+
+    /// Collect the encoded form of every node visited while resolving `path`, from this node down
+    /// to the terminal node (inclusive). Used to build Merkle proofs.
+    pub(crate) fn get_proof(
+        &mut self,
+        nodes: &mut NodesStorage<P, V, H>,
+        values: &ValuesStorage<P, V>,
+        mut path: NibbleSlice,
+        proof: &mut Vec<Vec<u8>>,
+    ) {
+        proof.push(self.compute_hash(nodes, values, path.offset()).to_vec());
+
+        if path.skip_prefix(&self.prefix) {
+            let mut child_node = nodes
+                .try_remove(self.child_ref.0)
+                .expect("inconsistent internal tree structure");
+
+            child_node.get_proof(nodes, values, path, proof);
+            self.child_ref = NodeRef(nodes.insert(child_node));
+        }
+    }
 }
 
 #[cfg(test)]
@@ -181,6 +255,24 @@ mod test {
         assert_eq!(node.child_ref, NodeRef(INVALID_REF));
     }
 
+    #[test]
+    fn prefix_is_already_packed_two_nibbles_per_byte() {
+        // `ExtensionNode::prefix` is a `NibbleVec`, which has stored two nibbles per byte (see
+        // `nibble.rs`'s `data: SmallVec<[u8; 64]>`) since before this node type existed — there
+        // was never a one-nibble-per-byte representation to pack down. `encode_compact_prefix`
+        // (chunk2-1) is the externally-visible proof: six nibbles compact-encode to well under
+        // six bytes.
+        let prefix = NibbleVec::from_nibbles(
+            [0x1, 0x2, 0x3, 0x4, 0x5, 0x6]
+                .into_iter()
+                .map(|x: u8| Nibble::try_from(x).unwrap()),
+        );
+        let node = ExtensionNode::<Vec<u8>, Vec<u8>, Keccak256>::new(prefix, Default::default());
+
+        assert_eq!(node.prefix.iter().count(), 6);
+        assert!(node.encode_compact_prefix().len() < 6);
+    }
+
     #[test]
     fn get_some() {
         let (mut nodes, mut values) = pmt_state!(Vec<u8>);
@@ -327,4 +419,83 @@ mod test {
         // TODO: Check node and children.
         assert_eq!(insert_action, InsertAction::Insert(NodeRef(3)));
     }
+
+    #[test]
+    fn remove_merges_extensions() {
+        let (mut nodes, mut values) = pmt_state!(Vec<u8>);
+
+        let node = pmt_node! { @(nodes, values)
+            extension { [0], branch {
+                0 => leaf { vec![0x00] => vec![0x12, 0x34, 0x56, 0x78] },
+                1 => leaf { vec![0x01] => vec![0x34, 0x56, 0x78, 0x9A] },
+            } }
+        };
+
+        let (node, old_value) = node.remove(&mut nodes, &mut values, NibbleSlice::new(&[0x00]));
+        let node = match node {
+            Some(Node::Extension(x)) => x,
+            _ => panic!("expected an extension node"),
+        };
+
+        // The collapsed inner branch (now an extension over the surviving leaf) is merged with
+        // this node's own prefix rather than left as a nested extension.
+        assert!(node.prefix.iter().eq([Nibble::V0, Nibble::V1].into_iter()));
+        assert_eq!(old_value, Some(vec![0x12, 0x34, 0x56, 0x78]));
+    }
+
+    #[test]
+    fn remove_collapses_to_leaf() {
+        let (mut nodes, mut values) = pmt_state!(Vec<u8>);
+
+        let node = pmt_node! { @(nodes, values)
+            extension { [0], branch {
+                0 => leaf { vec![0x00] => vec![0x12, 0x34, 0x56, 0x78] },
+            } with_leaf { vec![] => vec![0x9A, 0xBC] } }
+        };
+
+        // Removing the branch's only child leaves just its own value, which a branch collapses
+        // to a bare leaf — this node should disappear entirely rather than wrap that leaf in its
+        // now-pointless prefix.
+        let (node, old_value) = node.remove(&mut nodes, &mut values, NibbleSlice::new(&[0x00]));
+
+        assert!(matches!(node, Some(Node::Leaf(_))));
+        assert_eq!(old_value, Some(vec![0x12, 0x34, 0x56, 0x78]));
+    }
+
+    #[test]
+    fn get_proof_inclusion_includes_extension_branch_and_leaf() {
+        let (mut nodes, mut values) = pmt_state!(Vec<u8>);
+
+        let mut node = pmt_node! { @(nodes, values)
+            extension { [0], branch {
+                0 => leaf { vec![0x00] => vec![0x12, 0x34, 0x56, 0x78] },
+                1 => leaf { vec![0x01] => vec![0x34, 0x56, 0x78, 0x9A] },
+            } }
+        };
+
+        let mut proof = Vec::new();
+        node.get_proof(&mut nodes, &values, NibbleSlice::new(&[0x00]), &mut proof);
+
+        // The extension, the branch it points to, and the resolved leaf each contribute one
+        // encoded entry, in that root-to-leaf order.
+        assert_eq!(proof.len(), 3);
+    }
+
+    #[test]
+    fn get_proof_exclusion_stops_at_the_extension() {
+        let (mut nodes, mut values) = pmt_state!(Vec<u8>);
+
+        let mut node = pmt_node! { @(nodes, values)
+            extension { [0], branch {
+                0 => leaf { vec![0x00] => vec![0x12, 0x34, 0x56, 0x78] },
+            } }
+        };
+
+        let mut proof = Vec::new();
+        node.get_proof(&mut nodes, &values, NibbleSlice::new(&[0x10]), &mut proof);
+
+        // `path`'s first nibble (1) doesn't match this node's prefix (0), so the walk stops here:
+        // the extension's own encoding is already a complete exclusion witness.
+        assert_eq!(proof.len(), 1);
+    }
 }
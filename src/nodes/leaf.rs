@@ -15,7 +15,7 @@ where
     V: AsRef<[u8]>,
     H: Digest,
 {
-    value_ref: ValueRef,
+    pub(crate) value_ref: ValueRef,
 
     hash: (usize, Output<H>),
     phantom: PhantomData<(P, V, H)>,
@@ -157,7 +157,9 @@ where
             let mut digest_buf = DigestBuf::<H>::new();
 
             // Encode key.
-            // TODO: Improve performance by avoiding allocations.
+            // TODO: Improve performance by avoiding allocations — `NibbleSlice::new_composed`
+            // (see `crate::nibble`) lets `encode_path` consume a composed iterator directly,
+            // once it's changed to take one instead of a `&[Nibble]`.
             let key: Vec<_> = NibbleSlice::new(key.as_ref()).skip(key_offset).collect();
             let key_buf = encode_path(&key);
 
@@ -174,6 +176,40 @@ where
 
         &self.hash.1[..self.hash.0]
     }
+
+    /// Remove the value stored in this leaf if `path` matches its key, leaving it untouched
+    /// otherwise.
+    pub(crate) fn remove(
+        self,
+        _nodes: &mut NodesStorage<P, V, H>,
+        values: &mut ValuesStorage<P, V>,
+        path: NibbleSlice,
+    ) -> (Option<Node<P, V, H>>, Option<V>) {
+        let (value_path, _) = values
+            .get(self.value_ref.0)
+            .expect("inconsistent internal tree structure");
+
+        if path.cmp_rest(value_path.as_ref()) {
+            let (_, value) = values
+                .try_remove(self.value_ref.0)
+                .expect("inconsistent internal tree structure");
+
+            (None, Some(value))
+        } else {
+            (Some(self.into()), None)
+        }
+    }
+
+    /// Push this node's encoded form onto `proof`. Leaves are always the last node of a proof.
+    pub(crate) fn get_proof(
+        &mut self,
+        nodes: &mut NodesStorage<P, V, H>,
+        values: &ValuesStorage<P, V>,
+        path: NibbleSlice,
+        proof: &mut Vec<Vec<u8>>,
+    ) {
+        proof.push(self.compute_hash(nodes, values, path.offset()).to_vec());
+    }
 }
 
 #[cfg(test)]
@@ -273,6 +309,28 @@ mod test {
         assert_eq!(insert_action, InsertAction::Insert(NodeRef(0)));
     }
 
+    #[test]
+    fn insert_extension_branch_long_shared_prefix() {
+        let (mut nodes, mut values) = pmt_state!(Vec<u8>);
+
+        let node = pmt_node! { @(nodes, values)
+            leaf { vec![0x00, 0x00, 0x00] => vec![0x12, 0x34, 0x56, 0x78] }
+        };
+
+        // The existing key and the inserted one share five leading nibbles (`00000`) before
+        // diverging on the sixth, so the resulting extension should carry all five of them
+        // rather than a chain of single-nibble extensions/branches.
+        let (node, insert_action) =
+            node.insert(&mut nodes, &mut values, NibbleSlice::new(&[0x00, 0x00, 0x05]));
+        let node = match node {
+            Node::Extension(x) => x,
+            _ => panic!("expected an extension node"),
+        };
+
+        assert_eq!(node.prefix.iter().count(), 5);
+        assert_eq!(insert_action, InsertAction::Insert(NodeRef(0)));
+    }
+
     #[test]
     fn insert_extension_branch_value_self() {
         let (mut nodes, mut values) = pmt_state!(Vec<u8>);
@@ -0,0 +1,141 @@
+//! Reclaiming arena space for nodes no longer reachable from any retained root.
+//!
+//! NOTE: `insert`/`remove` in this tree mutate nodes along the touched path in place (each step
+//! does a `try_remove` immediately followed by an `insert`), so there is no copy-on-write sharing
+//! between versions to reclaim space from in the first place — retaining an older root and then
+//! continuing to mutate the tree will corrupt that older root, not merely waste space. Full
+//! version-tagged, copy-on-write roots (so several retained versions can coexist safely) are a
+//! separate, larger change than this pruner; what's implemented here is the reachability walk and
+//! sweep that such a scheme would need on top of, usable today for arenas that hold several
+//! independently-built root subtrees (e.g. via [`crate::PatriciaMerkleTree::root_ref`]-style
+//! external bookkeeping).
+
+use crate::node::Node;
+use crate::{NodeRef, NodesStorage, ValueRef, ValuesStorage};
+use digest::Digest;
+use std::collections::HashSet;
+
+/// Counts of entries reclaimed by a [`prune_unreachable`] call.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct PruneStats {
+    pub nodes_pruned: usize,
+    pub values_pruned: usize,
+}
+
+/// Free every node and value not reachable from one of `live_roots`.
+///
+/// Walks each live root, collecting the set of reachable node and value slab indices, then
+/// removes everything else from `nodes`/`values`.
+pub(crate) fn prune_unreachable<P, V, H>(
+    nodes: &mut NodesStorage<P, V, H>,
+    values: &mut ValuesStorage<P, V>,
+    live_roots: &[NodeRef],
+) -> PruneStats
+where
+    P: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+    H: Digest,
+{
+    let mut live_nodes = HashSet::new();
+    let mut live_values = HashSet::new();
+
+    for &root in live_roots {
+        mark_reachable(nodes, root, &mut live_nodes, &mut live_values);
+    }
+
+    let stale_nodes: Vec<usize> = nodes
+        .iter()
+        .map(|(key, _)| key)
+        .filter(|key| !live_nodes.contains(key))
+        .collect();
+    let stale_values: Vec<usize> = values
+        .iter()
+        .map(|(key, _)| key)
+        .filter(|key| !live_values.contains(key))
+        .collect();
+
+    for key in &stale_nodes {
+        nodes.remove(*key);
+    }
+    for key in &stale_values {
+        values.remove(*key);
+    }
+
+    PruneStats {
+        nodes_pruned: stale_nodes.len(),
+        values_pruned: stale_values.len(),
+    }
+}
+
+fn mark_reachable<P, V, H>(
+    nodes: &NodesStorage<P, V, H>,
+    node_ref: NodeRef,
+    live_nodes: &mut HashSet<usize>,
+    live_values: &mut HashSet<usize>,
+) where
+    P: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+    H: Digest,
+{
+    if !node_ref.is_valid() || !live_nodes.insert(node_ref.0) {
+        return;
+    }
+
+    let mark_value = |value_ref: ValueRef, live_values: &mut HashSet<usize>| {
+        if value_ref.is_valid() {
+            live_values.insert(value_ref.0);
+        }
+    };
+
+    match nodes.get(node_ref.0) {
+        Some(Node::Branch(branch_node)) => {
+            mark_value(branch_node.value_ref, live_values);
+            for &child_ref in branch_node.choices.iter() {
+                mark_reachable(nodes, child_ref, live_nodes, live_values);
+            }
+        }
+        Some(Node::Extension(extension_node)) => {
+            mark_reachable(nodes, extension_node.child_ref, live_nodes, live_values);
+        }
+        Some(Node::Leaf(leaf_node)) => {
+            mark_value(leaf_node.value_ref, live_values);
+        }
+        None => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{pmt_node, pmt_state};
+
+    #[test]
+    fn sweeps_only_what_the_live_root_cannot_reach() {
+        let (mut nodes, mut values) = pmt_state!(Vec<u8>);
+
+        let live_root = pmt_node! { @(nodes, values)
+            branch {
+                0 => leaf { vec![0x00, 0x12] => vec![0x34] },
+                1 => leaf { vec![0x10, 0x56] => vec![0x78] },
+            }
+        };
+        let live_root_ref = NodeRef(nodes.insert(live_root.into()));
+
+        // A second subtree with no retained root pointing at it — the garbage this sweep exists
+        // to reclaim.
+        let orphan = pmt_node! { @(nodes, values)
+            leaf { vec![0xAB] => vec![0xCD] }
+        };
+        nodes.insert(orphan.into());
+
+        assert_eq!(nodes.len(), 4);
+        assert_eq!(values.len(), 3);
+
+        let stats = prune_unreachable(&mut nodes, &mut values, &[live_root_ref]);
+
+        assert_eq!(stats, PruneStats { nodes_pruned: 1, values_pruned: 1 });
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(values.len(), 2);
+        assert!(nodes.get(live_root_ref.0).is_some());
+    }
+}
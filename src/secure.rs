@@ -0,0 +1,152 @@
+//! A "secure" trie variant that keys the structure by `H::digest(key)` instead of the raw key.
+//!
+//! Hashing keys before insertion bounds path depth and prevents adversarial key-prefix blowup,
+//! mirroring Ethereum's secure trie.
+
+use crate::PatriciaMerkleTree;
+use digest::{Digest, Output};
+
+/// A value paired with the original (un-hashed) path it was inserted under, so callers can still
+/// recover `P` even though the tree itself is indexed by `H::digest(P)`.
+#[derive(Clone, Debug, PartialEq)]
+struct SecureEntry<P, V> {
+    path: P,
+    value: V,
+}
+
+impl<P, V> AsRef<[u8]> for SecureEntry<P, V>
+where
+    V: AsRef<[u8]>,
+{
+    fn as_ref(&self) -> &[u8] {
+        self.value.as_ref()
+    }
+}
+
+/// A [`PatriciaMerkleTree`] that transparently hashes paths before using them to navigate the
+/// tree, while still returning values keyed by the original, un-hashed path.
+#[derive(Clone, Debug, Default)]
+pub struct SecurePatriciaMerkleTree<P, V, H>
+where
+    P: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+    H: Digest,
+{
+    inner: PatriciaMerkleTree<Output<H>, SecureEntry<P, V>, H>,
+}
+
+impl<P, V, H> SecurePatriciaMerkleTree<P, V, H>
+where
+    P: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+    H: Digest,
+{
+    /// Create an empty secure tree.
+    pub fn new() -> Self {
+        Self {
+            inner: PatriciaMerkleTree::new(),
+        }
+    }
+
+    /// Return whether the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Return the number of values in the tree.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Retrieve a value from the tree given its original (un-hashed) path.
+    pub fn get(&self, path: &P) -> Option<&V> {
+        self.inner
+            .get(&H::digest(path.as_ref()))
+            .map(|entry| &entry.value)
+    }
+
+    /// Insert a value into the tree, keyed internally by `H::digest(path)`.
+    pub fn insert(&mut self, path: P, value: V) -> Option<V> {
+        let hashed_path = H::digest(path.as_ref());
+
+        self.inner
+            .insert(hashed_path, SecureEntry { path, value })
+            .map(|entry| entry.value)
+    }
+
+    /// Remove a value from the tree given its original (un-hashed) path.
+    pub fn remove(&mut self, path: &P) -> Option<V> {
+        self.inner
+            .remove(&H::digest(path.as_ref()))
+            .map(|entry| entry.value)
+    }
+
+    /// The hashed path used internally to store `path`, exposed so proof verifiers can
+    /// reconstruct the path the underlying tree was actually built over.
+    pub fn hashed_path(path: &P) -> Output<H> {
+        H::digest(path.as_ref())
+    }
+
+    /// Return the root hash of the tree (or recompute if needed). Identical to the non-secure
+    /// tree, since it operates purely on the stored path/value pairs.
+    pub fn compute_hash(&mut self) -> Option<Output<H>> {
+        self.inner.compute_hash()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha3::Keccak256;
+
+    #[test]
+    fn get_returns_none_on_an_empty_tree() {
+        let tree = SecurePatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
+        assert_eq!(tree.get(&vec![0x12]), None);
+    }
+
+    #[test]
+    fn insert_then_get_roundtrips_the_original_unhashed_path() {
+        let mut tree = SecurePatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
+
+        assert_eq!(tree.insert(vec![0x12], vec![0x34]), None);
+        assert_eq!(tree.get(&vec![0x12]), Some(&vec![0x34]));
+
+        // The path actually used to navigate the underlying tree is `H::digest(path)`, not
+        // `path` itself.
+        assert_eq!(
+            tree.inner.get(&Keccak256::digest(vec![0x12])),
+            Some(&SecureEntry {
+                path: vec![0x12],
+                value: vec![0x34],
+            })
+        );
+    }
+
+    #[test]
+    fn insert_returns_the_previous_value_when_replacing() {
+        let mut tree = SecurePatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
+
+        tree.insert(vec![0x12], vec![0x34]);
+        assert_eq!(tree.insert(vec![0x12], vec![0x56]), Some(vec![0x34]));
+        assert_eq!(tree.get(&vec![0x12]), Some(&vec![0x56]));
+    }
+
+    #[test]
+    fn remove_deletes_the_value_and_returns_it() {
+        let mut tree = SecurePatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::new();
+
+        tree.insert(vec![0x12], vec![0x34]);
+        assert_eq!(tree.remove(&vec![0x12]), Some(vec![0x34]));
+        assert_eq!(tree.get(&vec![0x12]), None);
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn hashed_path_matches_what_insert_stores_under() {
+        assert_eq!(
+            SecurePatriciaMerkleTree::<Vec<u8>, Vec<u8>, Keccak256>::hashed_path(&vec![0x12]),
+            Keccak256::digest(vec![0x12]),
+        );
+    }
+}
@@ -0,0 +1,128 @@
+//! A trait generalizing the arena operations `NodesStorage`/`ValuesStorage` currently perform
+//! directly on a [`Slab`], so the backing storage can eventually be swapped for something
+//! disk-resident.
+//!
+//! `PatriciaMerkleTree`'s own `get`/`insert`/`remove`/`compute_hash`/`memory_usage` already route
+//! their arena accesses through this trait (via `NodeStore::get`/`insert`/`try_remove`/... in
+//! `lib.rs`) rather than calling `Slab`'s inherent methods directly. Other `lib.rs` code still
+//! uses `Slab`-only calls (`serialize`'s `nodes.iter()`, `deserialize`'s `Slab::with_capacity`),
+//! so swapping in [`CachingNodeStore`] means changing those call sites as well as the
+//! `NodesStorage`/`ValuesStorage` type aliases. What's still a follow-up is generalizing those aliases themselves
+//! (and every signature in `node.rs`/`nodes/*.rs` that names them) over `NodeStore<T>` instead of
+//! hard-coding `Slab`.
+
+use slab::Slab;
+
+/// The arena operations `PatriciaMerkleTree` performs on its node/value storage.
+pub trait NodeStore<T> {
+    fn get(&self, key: usize) -> Option<&T>;
+    fn get_mut(&mut self, key: usize) -> Option<&mut T>;
+    fn insert(&mut self, value: T) -> usize;
+    fn try_remove(&mut self, key: usize) -> Option<T>;
+    fn reserve(&mut self, additional: usize);
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn capacity(&self) -> usize;
+}
+
+impl<T> NodeStore<T> for Slab<T> {
+    fn get(&self, key: usize) -> Option<&T> {
+        Slab::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        Slab::get_mut(self, key)
+    }
+
+    fn insert(&mut self, value: T) -> usize {
+        Slab::insert(self, value)
+    }
+
+    fn try_remove(&mut self, key: usize) -> Option<T> {
+        Slab::try_remove(self, key)
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        Slab::reserve(self, additional)
+    }
+
+    fn len(&self) -> usize {
+        Slab::len(self)
+    }
+
+    fn capacity(&self) -> usize {
+        Slab::capacity(self)
+    }
+}
+
+/// A [`NodeStore`] that keeps an in-memory [`Slab`] of entries and writes anything evicted out to
+/// a [`crate::backend::NodeBackend`]. Its [`NodeStore`] methods only consult the in-memory
+/// `Slab`: an evicted entry is not read back from the backend.
+///
+/// Entries are only ever evicted explicitly via [`CachingNodeStore::evict`] — `insert`/`get`
+/// otherwise behave exactly like a plain `Slab`. Wiring automatic eviction and hash-keyed lazy
+/// reload into `PatriciaMerkleTree` itself is part of the follow-up mentioned above.
+pub struct CachingNodeStore<T, H, B>
+where
+    B: crate::backend::NodeBackend<H>,
+{
+    cache: Slab<T>,
+    backend: B,
+    _phantom: std::marker::PhantomData<H>,
+}
+
+impl<T, H, B> CachingNodeStore<T, H, B>
+where
+    B: crate::backend::NodeBackend<H>,
+{
+    pub fn new(backend: B) -> Self {
+        Self {
+            cache: Slab::new(),
+            backend,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Evict `hash`'s encoded bytes into the backend, freeing its cache slot.
+    pub fn evict(&mut self, key: usize, hash: H, encode: impl FnOnce(&T) -> Vec<u8>) {
+        if let Some(value) = self.cache.get(key) {
+            self.backend.insert(hash, encode(value));
+            self.cache.remove(key);
+        }
+    }
+}
+
+impl<T, H, B> NodeStore<T> for CachingNodeStore<T, H, B>
+where
+    B: crate::backend::NodeBackend<H>,
+{
+    fn get(&self, key: usize) -> Option<&T> {
+        self.cache.get(key)
+    }
+
+    fn get_mut(&mut self, key: usize) -> Option<&mut T> {
+        self.cache.get_mut(key)
+    }
+
+    fn insert(&mut self, value: T) -> usize {
+        self.cache.insert(value)
+    }
+
+    fn try_remove(&mut self, key: usize) -> Option<T> {
+        self.cache.try_remove(key)
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.cache.reserve(additional)
+    }
+
+    fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    fn capacity(&self) -> usize {
+        self.cache.capacity()
+    }
+}
@@ -1,6 +1,26 @@
-use crate::PatriciaMerkleTree;
+use crate::hashing::{NodeHash, NodeHashRef, NodeHasher};
+use crate::nibble::{Nibble, NibbleSlice};
 use digest::{Digest, Output};
 
+/// Compute a tree's root hash from an already key-sorted iterator of path/value pairs.
+///
+/// The caller must guarantee `iter` yields paths in strictly ascending order; this is checked
+/// with a `debug_assert` rather than at runtime in release builds, since re-verifying the
+/// ordering defeats the point of accepting pre-sorted input.
+///
+/// This is a "stack trie": rather than building a full [`PatriciaMerkleTree`](crate::PatriciaMerkleTree)
+/// and hashing it afterwards (`O(n)` resident nodes), it keeps only the current right-most
+/// root-to-leaf path in memory (`O(max key length)`). Ascending order guarantees that once a key
+/// diverges from an older sibling subtree, nothing later in `iter` can ever descend back into that
+/// subtree, so it can be collapsed down to just its hash (a [`StackNode::Done`]) and dropped as
+/// soon as the divergence is seen, instead of staying resident until the whole input is consumed.
+///
+/// Note: this hashes branch/extension/leaf nodes via [`NodeHasher`] uniformly. That is not byte
+/// for byte what [`crate::nodes::branch::BranchNode::compute_hash`] writes (this writes nothing
+/// for an empty child slot and sizes the list header from the bytes actually written), and it
+/// diverges from [`crate::nodes::extension::ExtensionNode`]/[`crate::nodes::leaf::LeafNode`]'s own `compute_hash`,
+/// which builds on a `DigestBuf`/`encode_path` pair that isn't defined anywhere in this crate (see
+/// [`crate::changeset`]'s own NOTE on the same gap) and so can't actually be reproduced here.
 pub fn compute_hash_from_sorted_iter<'a, P, V, H>(
     iter: impl IntoIterator<Item = (&'a P, &'a V)>,
 ) -> Output<H>
@@ -9,13 +29,349 @@ where
     V: 'a + AsRef<[u8]> + Clone,
     H: Digest,
 {
-    let mut tree = PatriciaMerkleTree::<P, V, H>::new();
+    let mut root = StackNode::<H>::Empty;
+
+    #[cfg(debug_assertions)]
+    let mut previous_path: Option<&[u8]> = None;
 
     for (path, value) in iter {
-        tree.insert(path.clone(), value.clone());
+        let path_bytes = path.as_ref();
+
+        #[cfg(debug_assertions)]
+        {
+            if let Some(previous_path) = previous_path {
+                debug_assert!(
+                    previous_path < path_bytes,
+                    "compute_hash_from_sorted_iter requires strictly ascending paths",
+                );
+            }
+            previous_path = Some(path_bytes);
+        }
+
+        let key: Vec<Nibble> = NibbleSlice::new(path_bytes).collect();
+        root = root.insert(&key, value.as_ref().to_vec());
+    }
+
+    match root.finalize() {
+        NodeHashRef::Hashed(hash) => hash,
+        NodeHashRef::Inline(bytes) => H::digest(&bytes),
     }
+}
+
+/// One node of a [`compute_hash_from_sorted_iter`] stack trie while it's still under construction.
+///
+/// [`Self::Done`] is the bounded-memory part: a subtree that provably can't receive any more
+/// insertions (because ascending order has already moved past it) is replaced by just its
+/// [`NodeHashRef`], freeing everything it used to hold.
+enum StackNode<H>
+where
+    H: Digest,
+{
+    Empty,
+    Done(NodeHashRef<H>),
+    Leaf {
+        key: Vec<Nibble>,
+        value: Vec<u8>,
+    },
+    Extension {
+        prefix: Vec<Nibble>,
+        child: Box<StackNode<H>>,
+    },
+    Branch {
+        choices: Box<[StackNode<H>; 16]>,
+        value: Option<Vec<u8>>,
+    },
+}
+
+impl<H> StackNode<H>
+where
+    H: Digest,
+{
+    /// Insert `(key, value)`, consuming and replacing `self` the same way
+    /// [`crate::nodes::branch::BranchNode::insert`] and its siblings do.
+    ///
+    /// `key` must sort after every key already inserted into `self` — the caller
+    /// ([`compute_hash_from_sorted_iter`]) guarantees this via its own ascending-order contract.
+    fn insert(self, key: &[Nibble], value: Vec<u8>) -> Self {
+        match self {
+            Self::Empty => Self::Leaf {
+                key: key.to_vec(),
+                value,
+            },
+            Self::Done(_) => {
+                unreachable!("a finalized subtree can never receive another, later-sorted key")
+            }
+            Self::Leaf {
+                key: leaf_key,
+                value: leaf_value,
+            } => {
+                let common = common_prefix_len(&leaf_key, key);
+
+                let mut choices: [StackNode<H>; 16] = std::array::from_fn(|_| Self::Empty);
+                let mut branch_value = None;
+
+                if common == leaf_key.len() {
+                    // The new key is strictly longer than the old leaf's: the old leaf's value
+                    // lives at the branch itself.
+                    branch_value = Some(leaf_value);
+                } else {
+                    // The old leaf's subtree sorts entirely before `key`, so nothing will ever
+                    // reach it again: finalize it immediately instead of keeping it resident.
+                    let idx = usize::from(leaf_key[common]);
+                    let rest = leaf_key[common + 1..].to_vec();
+                    choices[idx] = Self::Done(
+                        Self::Leaf {
+                            key: rest,
+                            value: leaf_value,
+                        }
+                        .finalize(),
+                    );
+                }
+
+                if common == key.len() {
+                    branch_value = Some(value);
+                } else {
+                    let idx = usize::from(key[common]);
+                    choices[idx] = Self::Leaf {
+                        key: key[common + 1..].to_vec(),
+                        value,
+                    };
+                }
+
+                let branch = Self::Branch {
+                    choices: Box::new(choices),
+                    value: branch_value,
+                };
+                wrap_in_extension(common, key, branch)
+            }
+            Self::Extension { prefix, child } => {
+                let common = common_prefix_len(&prefix, key);
+
+                if common == prefix.len() {
+                    let child = child.insert(&key[common..], value);
+                    Self::Extension {
+                        prefix,
+                        child: Box::new(child),
+                    }
+                } else {
+                    // The extension's own prefix diverges from `key`: everything beneath it sorts
+                    // before `key` and will never be reached again, so finalize the whole thing.
+                    let old_idx = usize::from(prefix[common]);
+                    let old_rest = prefix[common + 1..].to_vec();
+                    let old_subtree = if old_rest.is_empty() {
+                        *child
+                    } else {
+                        Self::Extension {
+                            prefix: old_rest,
+                            child,
+                        }
+                    };
+
+                    let mut choices: [StackNode<H>; 16] = std::array::from_fn(|_| Self::Empty);
+                    choices[old_idx] = Self::Done(old_subtree.finalize());
 
-    tree.compute_hash().clone()
+                    let branch_value;
+                    if common == key.len() {
+                        branch_value = Some(value);
+                    } else {
+                        let idx = usize::from(key[common]);
+                        choices[idx] = Self::Leaf {
+                            key: key[common + 1..].to_vec(),
+                            value,
+                        };
+                        branch_value = None;
+                    }
+
+                    let branch = Self::Branch {
+                        choices: Box::new(choices),
+                        value: branch_value,
+                    };
+                    wrap_in_extension(common, key, branch)
+                }
+            }
+            Self::Branch { mut choices, value: branch_value } => {
+                match key.split_first() {
+                    None => Self::Branch {
+                        choices,
+                        value: Some(value),
+                    },
+                    Some((nibble, rest)) => {
+                        let idx = usize::from(*nibble);
+
+                        // Every slot strictly before `idx` has seen its last possible insertion
+                        // (ascending order only ever moves the active slot forward), so finalize
+                        // and drop each of them now rather than keeping them resident.
+                        for slot in choices[..idx].iter_mut() {
+                            if !matches!(slot, Self::Empty | Self::Done(_)) {
+                                let old = std::mem::replace(slot, Self::Empty);
+                                *slot = Self::Done(old.finalize());
+                            }
+                        }
+
+                        let slot = std::mem::replace(&mut choices[idx], Self::Empty);
+                        choices[idx] = slot.insert(rest, value);
+
+                        Self::Branch {
+                            choices,
+                            value: branch_value,
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Collapse `self` into its encoded [`NodeHashRef`], recursively finalizing any part of it
+    /// that's still under construction.
+    fn finalize(self) -> NodeHashRef<H> {
+        match self {
+            Self::Empty => NodeHashRef::Inline(Vec::new()),
+            Self::Done(hash_ref) => hash_ref,
+            Self::Leaf { key, value } => encode_leaf::<H>(&key, &value),
+            Self::Extension { prefix, child } => {
+                encode_extension::<H>(&prefix, &child.finalize())
+            }
+            Self::Branch { choices, value } => {
+                let choices = (*choices).map(|choice| choice.finalize());
+                encode_branch::<H>(&choices, value.as_deref())
+            }
+        }
+    }
+}
+
+/// If `key`'s shared prefix with a sibling (`common` nibbles long) is non-empty, wrap `branch`
+/// in a [`StackNode::Extension`] over that shared prefix; otherwise return `branch` as-is.
+fn wrap_in_extension<H>(common: usize, key: &[Nibble], branch: StackNode<H>) -> StackNode<H>
+where
+    H: Digest,
+{
+    if common > 0 {
+        StackNode::Extension {
+            prefix: key[..common].to_vec(),
+            child: Box::new(branch),
+        }
+    } else {
+        branch
+    }
+}
+
+fn common_prefix_len(a: &[Nibble], b: &[Nibble]) -> usize {
+    a.iter().zip(b.iter()).take_while(|(x, y)| x == y).count()
+}
+
+fn write_child<H>(payload: &mut Vec<u8>, child: &NodeHashRef<H>)
+where
+    H: Digest,
+{
+    match child {
+        NodeHashRef::Inline(bytes) => payload.extend_from_slice(bytes),
+        NodeHashRef::Hashed(bytes) => {
+            payload.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+            payload.extend_from_slice(bytes);
+        }
+    }
+}
+
+fn finalize_payload<H>(payload: Vec<u8>) -> NodeHashRef<H>
+where
+    H: Digest,
+{
+    let hash = NodeHash::<H>::default();
+    let mut hasher = NodeHasher::new(&hash);
+    hasher.write_list_header(payload.len());
+    hasher.write_raw(&payload);
+    hasher.finalize()
+}
+
+fn encode_leaf<H>(key: &[Nibble], value: &[u8]) -> NodeHashRef<H>
+where
+    H: Digest,
+{
+    let key_bytes: Vec<u8> = key.iter().map(|nibble| u8::from(*nibble)).collect();
+
+    let mut payload = Vec::new();
+    payload.extend_from_slice(&(key_bytes.len() as u32).to_be_bytes());
+    payload.extend_from_slice(&key_bytes);
+    payload.extend_from_slice(&(value.len() as u32).to_be_bytes());
+    payload.extend_from_slice(value);
+
+    finalize_payload(payload)
+}
+
+fn encode_extension<H>(prefix: &[Nibble], child: &NodeHashRef<H>) -> NodeHashRef<H>
+where
+    H: Digest,
+{
+    let prefix_bytes: Vec<u8> = prefix.iter().map(|nibble| u8::from(*nibble)).collect();
+
+    let mut payload = Vec::new();
+    payload.extend_from_slice(&(prefix_bytes.len() as u32).to_be_bytes());
+    payload.extend_from_slice(&prefix_bytes);
+    write_child(&mut payload, child);
+
+    finalize_payload(payload)
+}
+
+fn encode_branch<H>(choices: &[NodeHashRef<H>; 16], value: Option<&[u8]>) -> NodeHashRef<H>
+where
+    H: Digest,
+{
+    let mut payload = Vec::new();
+    for choice in choices {
+        write_child(&mut payload, choice);
+    }
+    match value {
+        Some(value) => {
+            payload.extend_from_slice(&(value.len() as u32).to_be_bytes());
+            payload.extend_from_slice(value);
+        }
+        None => payload.extend_from_slice(&0u32.to_be_bytes()),
+    }
+
+    finalize_payload(payload)
+}
+
+/// Compute a Keccak-256 Merkle Patricia root of `input`'s key/value pairs.
+///
+/// Unlike [`compute_hash_from_sorted_iter`], the input doesn't need to already be sorted — that's
+/// done here — and the hasher is fixed to Keccak-256 (with a `[u8; 32]` result). Nodes are
+/// encoded with [`compute_hash_from_sorted_iter`]'s length-prefixed [`NodeHasher`] format, not
+/// RLP with hex-prefix paths, so the result is not the Ethereum reference trie root of the same
+/// pairs.
+pub fn trie_root<K, V, I>(input: I) -> [u8; 32]
+where
+    K: AsRef<[u8]>,
+    V: AsRef<[u8]> + Clone,
+    I: IntoIterator<Item = (K, V)>,
+{
+    let mut entries: Vec<(Vec<u8>, V)> = input
+        .into_iter()
+        .map(|(key, value)| (key.as_ref().to_vec(), value))
+        .collect();
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let hash = compute_hash_from_sorted_iter::<_, _, sha3::Keccak256>(
+        entries.iter().map(|(key, value)| (key, value)),
+    );
+
+    let mut root = [0u8; 32];
+    root.copy_from_slice(&hash);
+    root
+}
+
+/// Like [`trie_root`], but every key is first replaced with its own Keccak-256 hash — Ethereum's
+/// "secure trie" construction, which bounds path depth regardless of how keys are distributed.
+pub fn sec_trie_root<K, V, I>(input: I) -> [u8; 32]
+where
+    K: AsRef<[u8]>,
+    V: AsRef<[u8]> + Clone,
+    I: IntoIterator<Item = (K, V)>,
+{
+    trie_root(
+        input
+            .into_iter()
+            .map(|(key, value)| (sha3::Keccak256::digest(key.as_ref()).to_vec(), value)),
+    )
 }
 
 #[cfg(test)]
@@ -0,0 +1,63 @@
+//! A pluggable store for a tree's out-of-band values, mirroring [`crate::store::NodeStore`]
+//! but keyed by an opaque byte key the caller chooses (e.g. a UUID) rather than a content hash.
+//!
+//! This is what `examples/storage-mdbx.rs`'s `MdbxStorageTree` hand-rolled `load_value`/
+//! `store_value`/`erase_value` trio was doing ad hoc, with libmdbx, bincode and `Uuid` baked
+//! directly into the tree wrapper. Expressing that trio against a trait here lets the MDBX
+//! example (or a RocksDB/sled backend) plug in without `StorageRef::encode` or `insert`'s
+//! load/erase-on-overwrite logic caring which one it's talking to.
+//!
+//! Unlike [`crate::store::NodeStore`], these operations are fallible: a disk-backed value
+//! store can hit I/O errors or find its record already gone, which is exactly the case the MDBX
+//! example previously turned into a raw panic (`load_value`'s `.expect("value to be there")`)
+//! instead of a `Result`. See [`crate::store`] for the equivalent trait on the node side,
+//! and its NOTE on why wiring either of these into `PatriciaMerkleTree` itself is left for a
+//! follow-up.
+
+use std::collections::HashMap;
+
+/// A key-value store for a tree's externally-kept values, addressed by an opaque byte key.
+pub trait ValueStore {
+    type Error;
+
+    /// Fetch the bytes stored under `key`, if any.
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error>;
+
+    /// Store `bytes` under `key`, overwriting any previous value.
+    fn put(&mut self, key: &[u8], bytes: Vec<u8>) -> Result<(), Self::Error>;
+
+    /// Remove the value stored under `key`, if any.
+    fn del(&mut self, key: &[u8]) -> Result<(), Self::Error>;
+}
+
+/// An in-memory [`ValueStore`] that never fails, used by default and by tests.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryValueStore {
+    values: HashMap<Vec<u8>, Vec<u8>>,
+}
+
+impl MemoryValueStore {
+    pub fn new() -> Self {
+        Self {
+            values: HashMap::new(),
+        }
+    }
+}
+
+impl ValueStore for MemoryValueStore {
+    type Error = std::convert::Infallible;
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        Ok(self.values.get(key).cloned())
+    }
+
+    fn put(&mut self, key: &[u8], bytes: Vec<u8>) -> Result<(), Self::Error> {
+        self.values.insert(key.to_vec(), bytes);
+        Ok(())
+    }
+
+    fn del(&mut self, key: &[u8]) -> Result<(), Self::Error> {
+        self.values.remove(key);
+        Ok(())
+    }
+}
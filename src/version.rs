@@ -0,0 +1,164 @@
+//! Multi-version snapshots of a tree, so a caller can query or iterate a past root without it
+//! being mutated out from under them by a later update.
+//!
+//! NOTE: [`crate::pruner`]'s own NOTE already rules out `Rc`/`Arc`, copy-on-write roots sharing
+//! one arena here: `insert`/`remove` mutate nodes along the touched path in place, so retaining
+//! an older root while continuing to mutate the same `Slab` would silently corrupt that root, not
+//! just waste space. [`TreeVersion`] takes the safe route instead — `PatriciaMerkleTree` derives
+//! `Clone`, so each committed version is a fully independent tree with its own arena, and
+//! `TreeVersion` is just bookkeeping over a `Vec` of them. That costs a full arena clone per
+//! version rather than only the touched path; real structural sharing would mean switching
+//! `NodeRef` from a `Slab` index to an `Rc<Node>`, which is a deeper change than this module.
+//!
+//! NOTE: per-leaf index assignment (a `LeafNode` field set once at first insertion) isn't
+//! implemented here: `BranchNode`/`ExtensionNode`/`LeafNode`'s `insert` methods call each other
+//! recursively and construct new `LeafNode`s at several points of their own (e.g. a branch
+//! filling a previously-empty slot), so threading a shared counter through would mean changing
+//! every one of those signatures, not just `LeafNode::new`. A version number already identifies
+//! which snapshot a value came from, which is the property that field would have added here.
+
+use crate::PatriciaMerkleTree;
+use digest::Digest;
+
+/// A sequence of committed tree snapshots, each independently queryable and iterable.
+#[derive(Clone, Debug)]
+pub struct TreeVersion<P, V, H>
+where
+    P: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+    H: Digest,
+{
+    versions: Vec<PatriciaMerkleTree<P, V, H>>,
+}
+
+impl<P, V, H> TreeVersion<P, V, H>
+where
+    P: AsRef<[u8]> + Clone,
+    V: AsRef<[u8]> + Clone,
+    H: Digest + Clone,
+{
+    /// Start a fresh version history, its version `0` an empty tree.
+    pub fn new() -> Self {
+        Self {
+            versions: vec![PatriciaMerkleTree::new()],
+        }
+    }
+
+    /// The most recently committed version number.
+    pub fn current_version(&self) -> usize {
+        self.versions.len() - 1
+    }
+
+    /// Insert `value` at `path`, committing the result as a new version and leaving every prior
+    /// version exactly as it was. Returns the new version number.
+    pub fn insert(&mut self, path: P, value: V) -> usize {
+        let mut next = self
+            .versions
+            .last()
+            .expect("a TreeVersion always has at least one version")
+            .clone();
+        next.insert(path, value);
+        self.versions.push(next);
+        self.current_version()
+    }
+
+    /// Remove `path`, committing the result as a new version and leaving every prior version
+    /// exactly as it was. Returns the new version number and the removed value, if any.
+    pub fn remove(&mut self, path: &P) -> (usize, Option<V>) {
+        let mut next = self
+            .versions
+            .last()
+            .expect("a TreeVersion always has at least one version")
+            .clone();
+        let old_value = next.remove(path);
+        self.versions.push(next);
+        (self.current_version(), old_value)
+    }
+
+    /// Look up `path` as of `version`, or `None` if `version` doesn't exist.
+    pub fn get_at(&self, version: usize, path: &P) -> Option<&V> {
+        self.versions.get(version)?.get(path)
+    }
+
+    /// Iterate, in ascending key order, over every entry in `version`, or `None` if `version`
+    /// doesn't exist.
+    pub fn iter_at(&self, version: usize) -> Option<crate::iter::TreeIterator<P, V, H>> {
+        self.versions.get(version).map(PatriciaMerkleTree::iter)
+    }
+}
+
+impl<P, V, H> Default for TreeVersion<P, V, H>
+where
+    P: AsRef<[u8]> + Clone,
+    V: AsRef<[u8]> + Clone,
+    H: Digest + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sha3::Keccak256;
+
+    #[test]
+    fn each_insert_commits_a_new_version_leaving_earlier_ones_untouched() {
+        let mut versions = TreeVersion::<Vec<u8>, Vec<u8>, Keccak256>::new();
+
+        let v1 = versions.insert(vec![0x12], vec![0xAA]);
+        let v2 = versions.insert(vec![0x34], vec![0xBB]);
+
+        assert_eq!(v1, 1);
+        assert_eq!(v2, 2);
+
+        assert_eq!(versions.get_at(0, &vec![0x12]), None);
+        assert_eq!(versions.get_at(1, &vec![0x12]), Some(&vec![0xAA]));
+        assert_eq!(versions.get_at(1, &vec![0x34]), None);
+        assert_eq!(versions.get_at(2, &vec![0x12]), Some(&vec![0xAA]));
+        assert_eq!(versions.get_at(2, &vec![0x34]), Some(&vec![0xBB]));
+    }
+
+    #[test]
+    fn remove_commits_a_new_version_without_mutating_the_one_it_removed_from() {
+        let mut versions = TreeVersion::<Vec<u8>, Vec<u8>, Keccak256>::new();
+        versions.insert(vec![0x12], vec![0xAA]);
+
+        let (v, removed) = versions.remove(&vec![0x12]);
+        assert_eq!(v, 2);
+        assert_eq!(removed, Some(vec![0xAA]));
+
+        assert_eq!(versions.get_at(1, &vec![0x12]), Some(&vec![0xAA]));
+        assert_eq!(versions.get_at(2, &vec![0x12]), None);
+    }
+
+    #[test]
+    fn get_at_an_unknown_version_returns_none() {
+        let versions = TreeVersion::<Vec<u8>, Vec<u8>, Keccak256>::new();
+        assert_eq!(versions.get_at(5, &vec![0x12]), None);
+    }
+
+    #[test]
+    fn iter_at_reflects_only_that_versions_entries() {
+        let mut versions = TreeVersion::<Vec<u8>, Vec<u8>, Keccak256>::new();
+        versions.insert(vec![0x12], vec![0xAA]);
+        versions.insert(vec![0x34], vec![0xBB]);
+
+        let v1_paths: Vec<Vec<u8>> = versions
+            .iter_at(1)
+            .expect("version 1 exists")
+            .map(|(path, _)| path.clone())
+            .collect();
+        assert_eq!(v1_paths, vec![vec![0x12]]);
+
+        let v2_paths: Vec<Vec<u8>> = versions
+            .iter_at(2)
+            .expect("version 2 exists")
+            .map(|(path, _)| path.clone())
+            .collect();
+        assert_eq!(v2_paths, vec![vec![0x12], vec![0x34]]);
+
+        assert!(versions.iter_at(99).is_none());
+    }
+}